@@ -0,0 +1,134 @@
+//! Step-by-step construction of `Fixed` rings.
+
+use core::array;
+
+use super::fixed::Fixed;
+use super::{Box, Vec};
+
+/// Builder for `Fixed` rings.
+///
+/// Elements are produced in logical order, so `fill_with` sees logical indices
+/// whatever the rotation:
+///
+/// - `capacity(n)` sets the number of elements of a boxed or `Vec` ring.
+/// - `fill(x)` clones `x` into every slot, `fill_with(f)` calls `f(i)` for
+///   the element at logical index `i`.
+/// - `rotated(k)` places the oldest element at physical index `k`, so the
+///   next push writes there.
+/// - `boxed()`, `vec()` and `array::<N>()` pick the storage.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FixedBuilder<F = ()> {
+    capacity: Option<usize>,
+    rotation: usize,
+    fill: F,
+}
+
+impl FixedBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<F> FixedBuilder<F> {
+    #[inline]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    #[inline]
+    pub fn rotated(mut self, rotation: usize) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    #[inline]
+    pub fn fill<T>(self, value: T) -> FixedBuilder<impl FnMut(usize) -> T>
+    where
+        T: Clone,
+    {
+        self.fill_with(move |_| value.clone())
+    }
+
+    #[inline]
+    pub fn fill_with<T, G>(self, fill: G) -> FixedBuilder<G>
+    where
+        G: FnMut(usize) -> T,
+    {
+        FixedBuilder {
+            capacity: self.capacity,
+            rotation: self.rotation,
+            fill,
+        }
+    }
+}
+
+impl<T, F> FixedBuilder<F>
+where
+    F: FnMut(usize) -> T,
+{
+    /// Builds a ring over a `Vec`. Panics if the capacity is unset or zero.
+    pub fn vec(mut self) -> Fixed<Vec<T>> {
+        let capacity = self.capacity.expect("ring capacity not set");
+        assert!(capacity > 0, "a ring buffer needs at least one element");
+        let mut data: Vec<T> = (0..capacity).map(&mut self.fill).collect();
+        let first = self.rotation % capacity;
+        data.rotate_right(first);
+        Fixed::from_raw_parts(first, data)
+    }
+
+    /// Builds a ring over a boxed slice. Panics if the capacity is unset or
+    /// zero.
+    #[inline]
+    pub fn boxed(self) -> Fixed<Box<[T]>> {
+        let (first, data) = self.vec().into_raw_parts();
+        Fixed::from_raw_parts(first, data.into_boxed_slice())
+    }
+
+    /// Builds a ring over an array. Panics if `N` is zero or differs from a
+    /// capacity that was set.
+    pub fn array<const N: usize>(mut self) -> Fixed<[T; N]> {
+        assert!(N > 0, "a ring buffer needs at least one element");
+        assert!(self.capacity.is_none_or(|capacity| capacity == N));
+        let mut data: [T; N] = array::from_fn(&mut self.fill);
+        let first = self.rotation % N;
+        data.rotate_right(first);
+        Fixed::from_raw_parts(first, data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let rb = FixedBuilder::new().capacity(3).fill(7u8).boxed();
+        assert!(rb.iter().eq(&[7, 7, 7]));
+        let rb = FixedBuilder::new().fill(String::from("x")).array::<2>();
+        assert_eq!(rb.get(1), "x");
+    }
+
+    #[test]
+    fn test_fill_with_rotated() {
+        let mut rb = FixedBuilder::new()
+            .capacity(4)
+            .rotated(6)
+            .fill_with(|i| i * 10)
+            .vec();
+        assert_eq!(rb.first(), 2);
+        assert!(rb.iter().eq(&[0, 10, 20, 30]));
+        assert_eq!(rb.push(40), 0);
+        assert!(rb.iter().eq(&[10, 20, 30, 40]));
+
+        let rb = FixedBuilder::new().rotated(1).fill_with(|i| i).array::<3>();
+        assert_eq!(rb.into_raw_parts(), (1, [2, 0, 1]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_missing_capacity() {
+        FixedBuilder::new().fill(0).boxed();
+    }
+}
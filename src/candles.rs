@@ -0,0 +1,155 @@
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// Open/high/low/close summary of the ticks seen in one time interval.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Candle<P> {
+    /// Start of the interval, in the same unit as the tick timestamps.
+    pub start: u64,
+    pub open: P,
+    pub high: P,
+    pub low: P,
+    pub close: P,
+}
+
+impl<P> Candle<P>
+where
+    P: Copy + PartialOrd,
+{
+    #[inline]
+    pub fn new(start: u64, price: P) -> Self {
+        Candle {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+        }
+    }
+
+    #[inline]
+    pub fn update(&mut self, price: P) {
+        if price > self.high {
+            self.high = price;
+        }
+        if price < self.low {
+            self.low = price;
+        }
+        self.close = price;
+    }
+}
+
+/// Folds price ticks into a rolling history of candles.
+///
+/// Every slot of the backing storage holds one candle; the newest (still open)
+/// candle lives at the last logical index. Intervals without any ticks do not
+/// produce a candle.
+#[derive(Clone, Debug)]
+pub struct CandleAggregator<S> {
+    candles: Fixed<S>,
+    interval: u64,
+}
+
+impl<S, P> CandleAggregator<S>
+where
+    S: SliceMut<Element = Option<Candle<P>>>,
+    P: Copy + PartialOrd,
+{
+    /// Creates an aggregator over `storage`, which is cleared.
+    ///
+    /// Panics if `storage` is empty or `interval` is zero.
+    pub fn new(storage: S, interval: u64) -> Self {
+        assert!(interval > 0);
+        let mut candles = Fixed::from(storage);
+        candles.iter_mut().for_each(|candle| *candle = None);
+        CandleAggregator { candles, interval }
+    }
+
+    #[inline]
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Number of candles the history can hold, including the open one.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.candles.len()
+    }
+
+    /// Folds a tick into the candle of its interval.
+    ///
+    /// Returns the previous candle when the tick opens a new interval. Ticks
+    /// older than the open candle are folded into it.
+    pub fn push_tick(&mut self, time: u64, price: P) -> Option<Candle<P>> {
+        let start = time - time % self.interval;
+        let newest = self.candles.len() - 1;
+        match &mut self.candles[newest] {
+            Some(candle) if start <= candle.start => {
+                candle.update(price);
+                None
+            }
+            current => {
+                let closed = *current;
+                self.candles.push(Some(Candle::new(start, price)));
+                closed
+            }
+        }
+    }
+
+    /// The candle of the most recent interval that saw a tick.
+    #[inline]
+    pub fn current(&self) -> Option<&Candle<P>> {
+        self.candles[self.candles.len() - 1].as_ref()
+    }
+
+    /// Candles from the oldest to the current one.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Candle<P>>
+    where
+        P: 'a,
+    {
+        self.candles.iter().flatten()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.candles.iter_mut().for_each(|candle| *candle = None);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fold_ticks() {
+        let mut agg = CandleAggregator::new([None; 3], 60);
+        assert_eq!(agg.push_tick(0, 10), None);
+        assert_eq!(agg.push_tick(10, 12), None);
+        assert_eq!(agg.push_tick(20, 8), None);
+        assert_eq!(agg.push_tick(59, 11), None);
+        assert_eq!(
+            agg.current(),
+            Some(&Candle {
+                start: 0,
+                open: 10,
+                high: 12,
+                low: 8,
+                close: 11
+            })
+        );
+    }
+
+    #[test]
+    fn test_rolling_history() {
+        let mut agg = CandleAggregator::new(vec![None; 2], 10);
+        agg.push_tick(1, 1.0);
+        let closed = agg.push_tick(12, 2.0).unwrap();
+        assert_eq!(closed.start, 0);
+        agg.push_tick(35, 3.0);
+        agg.push_tick(33, 2.5);
+        let starts: Vec<u64> = agg.iter().map(|c| c.start).collect();
+        assert_eq!(starts, [10, 30]);
+        assert_eq!(agg.current().unwrap().low, 2.5);
+    }
+}
@@ -0,0 +1,226 @@
+//! Bounded in-memory packet capture, in the spirit of pcap.
+
+use super::clock::Clock;
+use super::record_ring::{RecordRing, Records};
+use super::ring_slice::RingSlice;
+use super::traits::{Slice, SliceMut};
+
+/// Bytes of the per-packet header: the timestamp as a little-endian `u64`,
+/// then the original length as a little-endian `u32`.
+pub const HEADER_LEN: usize = 12;
+
+/// Ring of captured packets with timestamps and drop accounting.
+///
+/// Every packet is stored as one record of a `RecordRing`, so capturing
+/// evicts whole oldest packets. Packets longer than the snapshot length are
+/// truncated to it; packets that still do not fit in the ring are dropped.
+#[derive(Clone, Debug)]
+pub struct PacketCapture<S, C> {
+    records: RecordRing<S>,
+    snaplen: Option<usize>,
+    clock: C,
+    stats: CaptureStats,
+}
+
+/// Counters of a `PacketCapture`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CaptureStats {
+    /// Packets stored, including the ones evicted since.
+    pub captured: u64,
+    /// Stored packets that were cut to the snapshot length.
+    pub truncated: u64,
+    /// Packets that did not fit in the ring at all.
+    pub dropped: u64,
+    /// Stored packets evicted by newer ones.
+    pub evicted: u64,
+}
+
+/// A captured packet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Packet<'a> {
+    /// Clock ticks when the packet was captured.
+    pub timestamp: u64,
+    /// Length of the packet before truncation.
+    pub orig_len: usize,
+    /// Captured bytes, at most the snapshot length.
+    pub data: RingSlice<'a, u8>,
+}
+
+impl Packet<'_> {
+    #[inline]
+    pub fn is_truncated(&self) -> bool {
+        self.data.len() < self.orig_len
+    }
+}
+
+impl<S, C> PacketCapture<S, C>
+where
+    S: Slice<Element = u8>,
+    C: Clock,
+{
+    /// Creates an empty capture over `storage`, truncating packets to
+    /// `snaplen` bytes if given.
+    #[inline]
+    pub fn new(storage: S, snaplen: Option<usize>, clock: C) -> Self {
+        PacketCapture {
+            records: RecordRing::new(storage),
+            snaplen,
+            clock,
+            stats: CaptureStats::default(),
+        }
+    }
+
+    #[inline]
+    pub fn snaplen(&self) -> Option<usize> {
+        self.snaplen
+    }
+
+    #[inline]
+    pub fn stats(&self) -> CaptureStats {
+        self.stats
+    }
+
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Number of packets held.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Packets, oldest first.
+    #[inline]
+    pub fn iter(&self) -> Packets<'_, S> {
+        Packets {
+            records: self.records.iter(),
+        }
+    }
+}
+
+impl<S, C> PacketCapture<S, C>
+where
+    S: SliceMut<Element = u8>,
+    C: Clock,
+{
+    /// Captures `packet` at the current time. Returns whether it was stored.
+    pub fn capture(&mut self, packet: &[u8]) -> bool {
+        let len = self
+            .snaplen
+            .map_or(packet.len(), |snaplen| packet.len().min(snaplen));
+        let mut header = [0; HEADER_LEN];
+        header[..8].copy_from_slice(&self.clock.now().to_le_bytes());
+        header[8..].copy_from_slice(&(packet.len().min(u32::MAX as usize) as u32).to_le_bytes());
+
+        match self.records.push_record_parts(&[&header, &packet[..len]]) {
+            Ok(evicted) => {
+                self.stats.captured += 1;
+                self.stats.truncated += u64::from(len < packet.len());
+                self.stats.evicted += evicted as u64;
+                true
+            }
+            Err(_) => {
+                self.stats.dropped += 1;
+                false
+            }
+        }
+    }
+
+    /// Drops every packet, keeping the counters.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+/// Iterator over the packets of a `PacketCapture`, oldest first.
+#[derive(Debug)]
+pub struct Packets<'a, S> {
+    records: Records<'a, S>,
+}
+
+impl<'a, S> Iterator for Packets<'a, S>
+where
+    S: Slice<Element = u8>,
+{
+    type Item = Packet<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (header, data) = self.records.next()?.split_at(HEADER_LEN);
+        let mut bytes = [0; HEADER_LEN];
+        header.copy_to_slice(&mut bytes);
+        let (timestamp, orig_len) = bytes.split_at(8);
+        Some(Packet {
+            timestamp: u64::from_le_bytes(timestamp.try_into().unwrap()),
+            orig_len: u32::from_le_bytes(orig_len.try_into().unwrap()) as usize,
+            data,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.records.size_hint()
+    }
+}
+
+impl<S> ExactSizeIterator for Packets<'_, S> where S: Slice<Element = u8> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn test_capture() {
+        let now = Cell::new(100);
+        let clock = || now.get();
+        // Room for two 4-byte packets: 2 * (4 + 12 + 4).
+        let mut capture = PacketCapture::new([0u8; 40], Some(4), &clock);
+        assert!(capture.capture(b"abcdef"));
+        now.set(200);
+        assert!(capture.capture(b"xy"));
+
+        let packets: Vec<_> = capture.iter().collect();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].timestamp, 100);
+        assert_eq!(packets[0].orig_len, 6);
+        assert!(packets[0].is_truncated());
+        assert!(packets[0].data.iter().eq(b"abcd"));
+        assert_eq!(packets[1].timestamp, 200);
+        assert!(!packets[1].is_truncated());
+        assert!(packets[1].data.iter().eq(b"xy"));
+
+        now.set(300);
+        assert!(capture.capture(b"1234"));
+        assert!(capture.iter().map(|packet| packet.timestamp).eq([200, 300]));
+        assert_eq!(
+            capture.stats(),
+            CaptureStats {
+                captured: 3,
+                truncated: 1,
+                dropped: 0,
+                evicted: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_drop_without_snaplen() {
+        let mut capture = PacketCapture::new(vec![0u8; 24], None, || 0);
+        assert!(capture.capture(&[1; 8]));
+        assert!(!capture.capture(&[2; 9]));
+        assert_eq!(capture.stats().dropped, 1);
+        assert_eq!(capture.len(), 1);
+
+        capture.clear();
+        assert!(capture.is_empty());
+        assert_eq!(capture.stats().captured, 1);
+    }
+}
@@ -0,0 +1,497 @@
+//! Bounded on-disk log that wraps around a fixed-size file.
+//!
+//! Records are framed with a 16-byte header (all integers little-endian):
+//!
+//! | offset | size | field                                 |
+//! |--------|------|---------------------------------------|
+//! | 0      | 2    | magic `b"RF"`                         |
+//! | 2      | 1    | kind: 0 = record, 1 = wrap marker     |
+//! | 3      | 1    | flags: bit 0 = checksummed            |
+//! | 4      | 4    | payload length                        |
+//! | 8      | 8    | sequence number                       |
+//!
+//! A checksummed record is followed by a CRC-32 of its header and payload.
+//! Wrap markers never carry one, so a marker fits in any gap of 16 bytes.
+//! Readers skip records whose checksum does not match (torn or corrupted
+//! writes) as long as the record after it confirms where it ended.
+//!
+//! When a record does not fit before the end of the file, a wrap marker is
+//! written (if there is room for one) and the record goes to offset 0,
+//! overwriting the oldest records. There is no index: on open the file is
+//! scanned for the chain of records written in the current lap, which starts at
+//! offset 0, and for the remains of the previous lap behind it.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use super::checksum::Crc32;
+use super::verify::{Report, Status};
+use super::Vec;
+
+const MAGIC: [u8; 2] = *b"RF";
+const KIND_RECORD: u8 = 0;
+const KIND_WRAP: u8 = 1;
+const FLAG_CHECKSUM: u8 = 1;
+const CHECKSUM_LEN: usize = 4;
+pub const FRAME_HEADER_LEN: usize = 16;
+
+/// How records are framed when they are appended.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Framing {
+    /// Header and payload only.
+    #[default]
+    Plain,
+    /// Every record carries a CRC-32 so damaged records can be skipped.
+    Checksummed,
+}
+
+/// One record read back from a `CircularFile`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Record {
+    pub seq: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Frame {
+    offset: usize,
+    kind: u8,
+    flags: u8,
+    len: usize,
+    seq: u64,
+    intact: bool,
+}
+
+impl Frame {
+    #[inline]
+    fn payload(&self) -> usize {
+        self.offset + FRAME_HEADER_LEN
+    }
+
+    #[inline]
+    fn end(&self) -> usize {
+        let checksum = if self.flags & FLAG_CHECKSUM != 0 {
+            CHECKSUM_LEN
+        } else {
+            0
+        };
+        self.payload() + self.len + checksum
+    }
+}
+
+/// Writer and reader of a circular log file.
+#[derive(Debug)]
+pub struct CircularFile {
+    file: File,
+    size: usize,
+    pos: usize,
+    next_seq: u64,
+    framing: Framing,
+}
+
+impl CircularFile {
+    /// Opens the log at `path`, creating a file of `size` bytes if it does not
+    /// exist, and positions the writer after the newest record.
+    #[inline]
+    pub fn open<P: AsRef<Path>>(path: P, size: usize) -> io::Result<Self> {
+        Self::open_with(path, size, Framing::Plain)
+    }
+
+    /// Like `open`, appending records with the given `framing`. Records already
+    /// in the file are read back whatever framing they were written with.
+    pub fn open_with<P: AsRef<Path>>(path: P, size: usize, framing: Framing) -> io::Result<Self> {
+        assert!(size > FRAME_HEADER_LEN + CHECKSUM_LEN);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let len = file.metadata()?.len();
+        if len == 0 {
+            file.set_len(size as u64)?;
+        } else if len != size as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "circular file has another size",
+            ));
+        }
+
+        let bytes = read_file(&mut file, size)?;
+        let frames = scan_frames(&bytes);
+        let (pos, next_seq) = match frames.last() {
+            Some(newest) => (newest.end(), newest.seq + 1),
+            None => (0, 0),
+        };
+        Ok(CircularFile {
+            file,
+            size,
+            pos,
+            next_seq,
+            framing,
+        })
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Largest payload a single record can carry.
+    #[inline]
+    pub fn max_record_len(&self) -> usize {
+        match self.framing {
+            Framing::Plain => self.size - FRAME_HEADER_LEN,
+            Framing::Checksummed => self.size - FRAME_HEADER_LEN - CHECKSUM_LEN,
+        }
+    }
+
+    /// Sequence number the next appended record will get.
+    #[inline]
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Appends a record, overwriting the oldest ones as needed, and returns its
+    /// sequence number.
+    pub fn append(&mut self, data: &[u8]) -> io::Result<u64> {
+        if data.len() > self.max_record_len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "record larger than the circular file",
+            ));
+        }
+
+        if self.pos + self.size - self.max_record_len() + data.len() > self.size {
+            if self.size - self.pos >= FRAME_HEADER_LEN {
+                self.write_frame(KIND_WRAP, &[])?;
+            }
+            self.pos = 0;
+        }
+
+        let seq = self.next_seq;
+        self.write_frame(KIND_RECORD, data)?;
+        self.next_seq += 1;
+        Ok(seq)
+    }
+
+    /// Reads every record still in the file, oldest first.
+    pub fn records(&mut self) -> io::Result<Vec<Record>> {
+        let bytes = read_file(&mut self.file, self.size)?;
+        Ok(scan(&bytes)
+            .into_iter()
+            .map(|frame| Record {
+                seq: frame.seq,
+                data: bytes[frame.payload()..frame.payload() + frame.len].to_vec(),
+            })
+            .collect())
+    }
+
+    /// Walks the file and reports which records can be read back and which are
+    /// damaged. Plain records are reported as unchecked.
+    pub fn verify(&mut self) -> io::Result<Report> {
+        let bytes = read_file(&mut self.file, self.size)?;
+        let frames = scan_frames(&bytes);
+        let mut report = Report::default();
+        for frame in &frames {
+            let status = match (frame.intact, frame.flags & FLAG_CHECKSUM != 0) {
+                (false, _) => Status::Corrupt,
+                (true, true) => Status::Intact,
+                (true, false) => Status::Unchecked,
+            };
+            report.push(frame.offset..frame.end(), status, Some(frame.seq));
+        }
+
+        // A torn write of the newest record ends the chain without being
+        // confirmed by a successor, so `scan_frames` leaves it out.
+        let (current, _) = chain(&bytes, 0);
+        let tail = current.last().map_or(0, Frame::end);
+        if let Some(torn) = parse_frame(&bytes, tail) {
+            let next = current.last().map_or(self.next_seq, |last| last.seq + 1);
+            if torn.kind == KIND_RECORD && !torn.intact && torn.seq == next {
+                report.push(torn.offset..torn.end(), Status::Corrupt, Some(torn.seq));
+            }
+        }
+        report.sort();
+        Ok(report)
+    }
+
+    /// Flushes written records to disk.
+    #[inline]
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn write_frame(&mut self, kind: u8, data: &[u8]) -> io::Result<()> {
+        let flags = match self.framing {
+            Framing::Checksummed if kind == KIND_RECORD => FLAG_CHECKSUM,
+            _ => 0,
+        };
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + data.len() + CHECKSUM_LEN);
+        frame.extend_from_slice(&MAGIC);
+        frame.push(kind);
+        frame.push(flags);
+        frame.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&self.next_seq.to_le_bytes());
+        frame.extend_from_slice(data);
+        if flags & FLAG_CHECKSUM != 0 {
+            let mut crc = Crc32::new();
+            crc.update(&frame);
+            frame.extend_from_slice(&crc.finish().to_le_bytes());
+        }
+        self.file.seek(SeekFrom::Start(self.pos as u64))?;
+        self.file.write_all(&frame)?;
+        self.pos += frame.len();
+        Ok(())
+    }
+}
+
+fn read_file(file: &mut File, size: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = alloc::vec![0; size];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn parse_frame(bytes: &[u8], offset: usize) -> Option<Frame> {
+    let header = bytes.get(offset..offset + FRAME_HEADER_LEN)?;
+    if header[..2] != MAGIC || header[2] > KIND_WRAP || header[3] & !FLAG_CHECKSUM != 0 {
+        return None;
+    }
+    let mut frame = Frame {
+        offset,
+        kind: header[2],
+        flags: header[3],
+        len: u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize,
+        seq: u64::from_le_bytes(header[8..16].try_into().unwrap()),
+        intact: true,
+    };
+    if frame.end() > bytes.len() {
+        return None;
+    }
+    if frame.flags & FLAG_CHECKSUM != 0 {
+        let checksum_at = frame.end() - CHECKSUM_LEN;
+        let mut crc = Crc32::new();
+        crc.update(&bytes[offset..checksum_at]);
+        frame.intact = bytes[checksum_at..frame.end()] == crc.finish().to_le_bytes();
+    }
+    Some(frame)
+}
+
+/// Follows consecutive records from `offset` until a wrap marker, the end of
+/// the file or a frame that does not continue the sequence.
+fn chain(bytes: &[u8], offset: usize) -> (Vec<Frame>, bool) {
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut offset = offset;
+    loop {
+        let frame = match parse_frame(bytes, offset) {
+            Some(frame) => frame,
+            None => return (frames, bytes.len() - offset < FRAME_HEADER_LEN),
+        };
+        if frame.kind == KIND_WRAP {
+            return (frames, true);
+        }
+        if frames.last().is_some_and(|last| frame.seq != last.seq + 1) {
+            return (frames, false);
+        }
+        // The length of a damaged record is only trusted if the record after
+        // it lines up.
+        if !frame.intact && !confirms(bytes, &frame) {
+            return (frames, false);
+        }
+        offset = frame.end();
+        frames.push(frame);
+    }
+}
+
+fn confirms(bytes: &[u8], frame: &Frame) -> bool {
+    match parse_frame(bytes, frame.end()) {
+        Some(next) => next.kind == KIND_WRAP || next.seq == frame.seq + 1,
+        None => bytes.len() - frame.end() < FRAME_HEADER_LEN,
+    }
+}
+
+/// Finds the live records of a circular file, oldest first, leaving out
+/// damaged ones.
+fn scan(bytes: &[u8]) -> Vec<Frame> {
+    let mut frames = scan_frames(bytes);
+    frames.retain(|frame| frame.intact);
+    frames
+}
+
+fn scan_frames(bytes: &[u8]) -> Vec<Frame> {
+    let (current, _) = chain(bytes, 0);
+    let lap_start = current.first().map(|frame| frame.seq);
+    let tail = current.last().map_or(0, Frame::end);
+
+    // The previous lap survives behind the current one as a chain that runs up
+    // to the end of the file and ends right before the current lap's first
+    // sequence number.
+    for offset in tail..bytes.len().saturating_sub(FRAME_HEADER_LEN) {
+        let (previous, complete) = chain(bytes, offset);
+        let last = match previous.last() {
+            Some(last) if complete => last.seq,
+            _ => continue,
+        };
+        if lap_start.is_none_or(|start| last + 1 == start) {
+            return previous.into_iter().chain(current).collect();
+        }
+    }
+    current
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::verify::Region;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_path() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!("rb-circular-{}-{}", std::process::id(), n))
+    }
+
+    fn payloads(file: &mut CircularFile) -> Vec<Vec<u8>> {
+        file.records()
+            .unwrap()
+            .into_iter()
+            .map(|record| record.data)
+            .collect()
+    }
+
+    #[test]
+    fn test_first_lap() {
+        let path = temp_path();
+        let mut file = CircularFile::open(&path, 256).unwrap();
+        assert_eq!(file.append(b"hello").unwrap(), 0);
+        assert_eq!(file.append(b"world").unwrap(), 1);
+        assert_eq!(payloads(&mut file), [b"hello", b"world"]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_wrap_and_recover() {
+        let path = temp_path();
+        {
+            let mut file = CircularFile::open(&path, 100).unwrap();
+            for i in 0..10u8 {
+                file.append(&[i; 10]).unwrap();
+            }
+        }
+        let mut file = CircularFile::open(&path, 100).unwrap();
+        assert_eq!(file.next_seq(), 10);
+        let records = file.records().unwrap();
+        let seqs: Vec<u64> = records.iter().map(|record| record.seq).collect();
+        assert_eq!(seqs, [7, 8, 9]);
+        assert_eq!(records[0].data, [7; 10]);
+
+        file.append(&[10; 30]).unwrap();
+        let seqs: Vec<u64> = file.records().unwrap().iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, [9, 10]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_checksummed_skips_damage() {
+        let path = temp_path();
+        {
+            let mut file = CircularFile::open_with(&path, 256, Framing::Checksummed).unwrap();
+            file.append(b"one").unwrap();
+            file.append(b"two").unwrap();
+            file.append(b"three").unwrap();
+        }
+        // Flip a payload byte of the middle record.
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[23 + FRAME_HEADER_LEN] ^= 0xff;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut file = CircularFile::open_with(&path, 256, Framing::Checksummed).unwrap();
+        let seqs: Vec<u64> = file.records().unwrap().iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, [0, 2]);
+        assert_eq!(file.append(b"four").unwrap(), 3);
+        assert_eq!(file.records().unwrap().last().unwrap().data, b"four");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_last_record() {
+        let path = temp_path();
+        {
+            let mut file = CircularFile::open_with(&path, 256, Framing::Checksummed).unwrap();
+            file.append(b"kept").unwrap();
+            file.append(b"torn record").unwrap();
+        }
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[24 + FRAME_HEADER_LEN + 4] = 0;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut file = CircularFile::open(&path, 256).unwrap();
+        assert_eq!(file.records().unwrap().len(), 1);
+        assert_eq!(file.next_seq(), 1);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_verify() {
+        let path = temp_path();
+        let mut file = CircularFile::open_with(&path, 256, Framing::Checksummed).unwrap();
+        file.append(b"one").unwrap();
+        file.append(b"two").unwrap();
+        file.append(b"three").unwrap();
+        assert!(file.verify().unwrap().is_clean());
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[23 + FRAME_HEADER_LEN] ^= 0xff;
+        bytes[46 + FRAME_HEADER_LEN] ^= 0xff;
+        std::fs::write(&path, &bytes).unwrap();
+        let report = file.verify().unwrap();
+        let corrupt: Vec<_> = report.corrupt().map(|region| region.seq).collect();
+        assert_eq!(corrupt, [Some(1), Some(2)]);
+        let recoverable: Vec<_> = report.recoverable().cloned().collect();
+        assert_eq!(
+            recoverable,
+            [Region {
+                range: 0..23,
+                status: Status::Intact,
+                seq: Some(0),
+            }]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_checksummed_wrap_in_short_gap() {
+        let path = temp_path();
+        {
+            let mut file = CircularFile::open_with(&path, 100, Framing::Checksummed).unwrap();
+            // Each record takes 41 bytes, leaving 18 before the end.
+            for i in 0..3u8 {
+                file.append(&[i; 21]).unwrap();
+            }
+        }
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 100);
+        let mut file = CircularFile::open_with(&path, 100, Framing::Checksummed).unwrap();
+        assert_eq!(file.next_seq(), 3);
+        let seqs: Vec<u64> = file.records().unwrap().iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, [1, 2]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_record_too_large() {
+        let path = temp_path();
+        let mut file = CircularFile::open(&path, 32).unwrap();
+        assert!(file.append(&[0; 17]).is_err());
+        assert!(file.append(&[0; 16]).is_ok());
+        assert!(CircularFile::open(&path, 64).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
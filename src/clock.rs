@@ -0,0 +1,51 @@
+/// Source of monotonically non-decreasing timestamps.
+///
+/// The unit of a tick is up to the implementation. Time-based types in this
+/// crate only compare and subtract ticks, so their windows and intervals must
+/// be expressed in the same unit as the clock they are given.
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> u64,
+{
+    #[inline]
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+/// Clock counting nanoseconds since its creation, backed by `std::time::Instant`.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub struct MonotonicClock {
+    origin: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl MonotonicClock {
+    #[inline]
+    pub fn new() -> Self {
+        MonotonicClock {
+            origin: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for MonotonicClock {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for MonotonicClock {
+    #[inline]
+    fn now(&self) -> u64 {
+        self.origin.elapsed().as_nanos() as u64
+    }
+}
@@ -0,0 +1,254 @@
+//! Operations over two rings.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::{FusedIterator, Peekable};
+
+use super::fixed::Fixed;
+use super::traits::{Slice, SliceMut};
+use super::Vec;
+
+impl<S> Fixed<S>
+where
+    S: SliceMut + IntoIterator<Item = <S as Slice>::Element>,
+{
+    /// Merges two rings sorted in logical order into one sorted ring holding
+    /// the elements of both. Equal elements from `self` come first.
+    pub fn merge_sorted<S2>(self, other: Fixed<S2>) -> Fixed<Vec<S::Element>>
+    where
+        S2: SliceMut<Element = S::Element> + IntoIterator<Item = S::Element>,
+        S::Element: Ord,
+    {
+        let mut merged = Vec::with_capacity(self.len() + other.len());
+        let mut left = self.into_inner_ordered().into_iter().peekable();
+        let mut right = other.into_inner_ordered().into_iter().peekable();
+        while let Some(item) = next_sorted(&mut left, &mut right) {
+            merged.push(item);
+        }
+        Fixed::from(merged)
+    }
+
+    /// Alternates the elements of both rings in logical order, starting with
+    /// `self`; the rest of the longer ring follows at the end.
+    pub fn interleave<S2>(self, other: Fixed<S2>) -> Fixed<Vec<S::Element>>
+    where
+        S2: SliceMut<Element = S::Element> + IntoIterator<Item = S::Element>,
+    {
+        let mut interleaved = Vec::with_capacity(self.len() + other.len());
+        let mut left = self.into_inner_ordered().into_iter();
+        let mut right = other.into_inner_ordered().into_iter();
+        loop {
+            match (left.next(), right.next()) {
+                (None, None) => break,
+                (a, b) => interleaved.extend(a.into_iter().chain(b)),
+            }
+        }
+        Fixed::from(interleaved)
+    }
+}
+
+/// Two rings that must be the same length are not.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LengthMismatch {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ring lengths differ: {} and {}", self.left, self.right)
+    }
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice,
+{
+    /// Pairs of elements of both rings at the same logical index, oldest
+    /// first. Fails if the lengths differ.
+    #[inline]
+    pub fn zip<'a, S2>(&'a self, other: &'a Fixed<S2>) -> Result<Zip<'a, S, S2>, LengthMismatch>
+    where
+        S2: Slice,
+    {
+        if self.len() != other.len() {
+            return Err(LengthMismatch {
+                left: self.len(),
+                right: other.len(),
+            });
+        }
+        Ok(Zip {
+            left: self,
+            right: other,
+            front: 0,
+            back: self.len(),
+        })
+    }
+}
+
+/// Iterator over two rings of the same length in lockstep.
+#[derive(Debug)]
+pub struct Zip<'a, A, B> {
+    left: &'a Fixed<A>,
+    right: &'a Fixed<B>,
+    front: usize,
+    back: usize,
+}
+
+impl<A, B> Clone for Zip<'_, A, B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Zip { ..*self }
+    }
+}
+
+impl<'a, A, B> Zip<'a, A, B>
+where
+    A: Slice,
+    B: Slice,
+{
+    #[inline]
+    fn pair(&self, index: usize) -> (&'a A::Element, &'a B::Element) {
+        let (left, right): (&'a Fixed<A>, &'a Fixed<B>) = (self.left, self.right);
+        (left.get(index), right.get(index))
+    }
+}
+
+impl<'a, A, B> Iterator for Zip<'a, A, B>
+where
+    A: Slice,
+    B: Slice,
+{
+    type Item = (&'a A::Element, &'a B::Element);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.pair(self.front - 1))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<A, B> DoubleEndedIterator for Zip<'_, A, B>
+where
+    A: Slice,
+    B: Slice,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.pair(self.back))
+    }
+}
+
+impl<A, B> ExactSizeIterator for Zip<'_, A, B>
+where
+    A: Slice,
+    B: Slice,
+{
+}
+
+impl<A, B> FusedIterator for Zip<'_, A, B>
+where
+    A: Slice,
+    B: Slice,
+{
+}
+
+fn next_sorted<T, A, B>(left: &mut Peekable<A>, right: &mut Peekable<B>) -> Option<T>
+where
+    T: Ord,
+    A: Iterator<Item = T>,
+    B: Iterator<Item = T>,
+{
+    match (left.peek(), right.peek()) {
+        (Some(a), Some(b)) if b.cmp(a) == Ordering::Less => right.next(),
+        (Some(_), _) => left.next(),
+        (None, _) => right.next(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_merge_sorted() {
+        let mut left = Fixed::from([0; 3]);
+        left.extend([1, 3, 5, 7]);
+        let mut right = Fixed::from(vec![0; 3]);
+        right.extend([0, 2, 3, 3, 8]);
+        let merged = left.merge_sorted(right);
+        assert!(merged.iter().eq(&[3, 3, 3, 5, 7, 8]));
+        assert_eq!(merged.first(), 0);
+    }
+
+    #[test]
+    fn test_merge_sorted_is_stable() {
+        #[derive(Debug, Clone, Copy)]
+        struct Key(u8, char);
+        impl PartialEq for Key {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Key {}
+        impl PartialOrd for Key {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Key {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let left = Fixed::from([Key(1, 'a'), Key(2, 'a')]);
+        let right = Fixed::from([Key(1, 'b'), Key(2, 'b')]);
+        let merged = left.merge_sorted(right);
+        assert!(merged.iter().map(|k| k.1).eq(['a', 'b', 'a', 'b']));
+    }
+
+    #[test]
+    fn test_zip() {
+        let mut timestamps = Fixed::from([0u64; 3]);
+        let mut values = Fixed::from(vec![0.0; 3]);
+        // Rotated differently from `timestamps`.
+        values.push(-1.0);
+        for (t, v) in [(1, 0.5), (2, 1.5), (3, 2.5), (4, 3.5)] {
+            timestamps.push(t);
+            values.push(v);
+        }
+        let mut zipped = timestamps.zip(&values).unwrap();
+        assert_eq!(zipped.len(), 3);
+        assert!(zipped.clone().eq([(&2, &1.5), (&3, &2.5), (&4, &3.5)]));
+        assert_eq!(zipped.next_back(), Some((&4, &3.5)));
+
+        let short = Fixed::from([0u8; 2]);
+        assert_eq!(
+            timestamps.zip(&short).err(),
+            Some(LengthMismatch { left: 3, right: 2 })
+        );
+    }
+
+    #[test]
+    fn test_interleave() {
+        let mut left = Fixed::from(vec![0; 3]);
+        left.extend([1, 2, 3, 4]);
+        let right = Fixed::from([10, 20, 30, 40, 50]);
+        let interleaved = left.interleave(right);
+        assert!(interleaved.iter().eq(&[2, 10, 3, 20, 4, 30, 40, 50]));
+    }
+}
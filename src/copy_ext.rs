@@ -0,0 +1,86 @@
+//! Fast paths for rings of `Copy` elements.
+//!
+//! The generic methods on `Fixed` move elements one at a time so they work
+//! for any element type. `CopyRingExt` adds bulk versions that compile down
+//! to at most two `memcpy`s, without specializing the core.
+
+use core::ops::RangeBounds;
+
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+use super::Vec;
+
+/// Bulk copies for rings of `Copy` elements.
+pub trait CopyRingExt<T: Copy> {
+    /// Pushes every element of `src`, oldest first. Only the last `len()`
+    /// elements of a longer `src` remain.
+    fn push_copy_slice(&mut self, src: &[T]);
+
+    /// The elements, oldest first, or `None` if `N` is not the ring length.
+    fn to_array<const N: usize>(&self) -> Option<[T; N]>;
+
+    /// The elements in a logical `range`, oldest first. Panics if the range
+    /// is out of bounds, like slicing.
+    fn clone_window<R>(&self, range: R) -> Vec<T>
+    where
+        R: RangeBounds<usize>;
+}
+
+impl<S> CopyRingExt<S::Element> for Fixed<S>
+where
+    S: SliceMut,
+    S::Element: Copy,
+{
+    #[inline]
+    fn push_copy_slice(&mut self, src: &[S::Element]) {
+        self.extend_from_slice(src);
+    }
+
+    #[inline]
+    fn to_array<const N: usize>(&self) -> Option<[S::Element; N]> {
+        if N != self.len() {
+            return None;
+        }
+        // A ring is never empty, so there is always an element to start from.
+        let mut array = [*self.get(0); N];
+        self.copy_to_slice(&mut array);
+        Some(array)
+    }
+
+    fn clone_window<R>(&self, range: R) -> Vec<S::Element>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (head, tail) = self.range(range).as_slices();
+        let mut window = Vec::with_capacity(head.len() + tail.len());
+        window.extend_from_slice(head);
+        window.extend_from_slice(tail);
+        window
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_copy_ext() {
+        let mut rb = Fixed::from([0u16; 4]);
+        rb.push_copy_slice(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(rb.to_array(), Some([3, 4, 5, 6]));
+        assert_eq!(rb.to_array::<3>(), None);
+
+        rb.push_copy_slice(&[7]);
+        assert_eq!(rb.first(), 1);
+        assert_eq!(rb.clone_window(1..3), [5, 6]);
+        assert_eq!(rb.clone_window(2..), [6, 7]);
+        assert_eq!(rb.clone_window(..), [4, 5, 6, 7]);
+        assert!(rb.clone_window(4..).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_clone_window_out_of_bounds() {
+        Fixed::from([0; 2]).clone_window(..3);
+    }
+}
@@ -0,0 +1,573 @@
+use core::fmt;
+use core::hash::Hash;
+use core::iter::{Cycle, Skip, Take};
+use core::num::Wrapping;
+use core::ops::Deref;
+use core::slice;
+
+use super::fixed::Fixed;
+use super::traits::{Slice, SliceMut};
+
+/// Push counter of a `Counted` ring, which decides what happens once it
+/// runs out of range.
+///
+/// Plain integers saturate: a ring whose counter reached its maximum refuses
+/// further pushes, see `Counted::try_push`. `Wrapping` integers start over
+/// at zero, and sequence numbers are compared by their distance from the
+/// counter, so they stay valid across the wrap as long as they are in the
+/// ring.
+pub trait SeqCounter: Copy + Default + fmt::Debug + Eq + Hash {
+    /// Counts `n` pushes. Returns `false`, leaving the counter unchanged, if
+    /// it cannot.
+    fn advance(&mut self, n: u64) -> bool;
+
+    /// Current count, which is the sequence number of the next push.
+    fn count(self) -> u64;
+
+    /// Number of pushes since sequence number `seq` was handed out, in the
+    /// counter's arithmetic. Out of range for a `seq` the counter never
+    /// produced.
+    fn distance(self, seq: u64) -> u64;
+
+    /// Sequence number handed out `n` pushes before the current count.
+    fn back(self, n: u64) -> u64;
+}
+
+macro_rules! impl_seq_counter {
+    ($($int:ty),*) => {$(
+        impl SeqCounter for $int {
+            #[inline]
+            fn advance(&mut self, n: u64) -> bool {
+                <$int>::try_from(n)
+                    .ok()
+                    .and_then(|n| self.checked_add(n))
+                    .map(|next| *self = next)
+                    .is_some()
+            }
+
+            #[inline]
+            fn count(self) -> u64 {
+                self as u64
+            }
+
+            #[inline]
+            fn distance(self, seq: u64) -> u64 {
+                (self as u64).wrapping_sub(seq)
+            }
+
+            #[inline]
+            fn back(self, n: u64) -> u64 {
+                self as u64 - n
+            }
+        }
+
+        impl SeqCounter for Wrapping<$int> {
+            #[inline]
+            fn advance(&mut self, n: u64) -> bool {
+                *self += n as $int;
+                true
+            }
+
+            #[inline]
+            fn count(self) -> u64 {
+                self.0 as u64
+            }
+
+            #[inline]
+            fn distance(self, seq: u64) -> u64 {
+                <$int>::try_from(seq).map_or(u64::MAX, |seq| self.0.wrapping_sub(seq) as u64)
+            }
+
+            #[inline]
+            fn back(self, n: u64) -> u64 {
+                self.0.wrapping_sub(n as $int) as u64
+            }
+        }
+    )*};
+}
+
+impl_seq_counter!(u32, u64);
+
+/// `Fixed` ring that numbers every pushed element with an absolute sequence
+/// number.
+///
+/// The `n`-th pushed element (counting from zero) has sequence number `n`.
+/// Elements the storage held before the first push have no sequence number.
+/// Read access goes through `Deref` to the inner ring; mutation is limited to
+/// the methods here so the counter cannot fall out of sync.
+///
+/// The counter `C` is a 64-bit saturating one by default; see `SeqCounter`
+/// for the alternatives.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Counted<S, C = u64> {
+    ring: Fixed<S>,
+    pushed: C,
+    /// Number of elements, newest first, that have a sequence number.
+    numbered: usize,
+}
+
+impl<S> Counted<S>
+where
+    S: Slice,
+{
+    #[inline]
+    pub fn new(ring: Fixed<S>) -> Self {
+        Self::with_counter(ring)
+    }
+}
+
+impl<S, C> Counted<S, C>
+where
+    S: Slice,
+    C: SeqCounter,
+{
+    /// Like `new`, with a counter other than the default.
+    #[inline]
+    pub fn with_counter(ring: Fixed<S>) -> Self {
+        Counted {
+            ring,
+            pushed: C::default(),
+            numbered: 0,
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Fixed<S> {
+        self.ring
+    }
+
+    /// Number of elements pushed since creation. Like the rest of the
+    /// counters, this starts over when a wrapping counter wraps.
+    #[inline]
+    pub fn total_pushed(&self) -> u64 {
+        self.pushed.count()
+    }
+
+    /// Number of pushed elements that have since been overwritten.
+    #[inline]
+    pub fn overwritten(&self) -> u64 {
+        self.oldest_seq()
+    }
+
+    /// Number of times the write position went all the way around the ring.
+    #[inline]
+    pub fn lap_count(&self) -> u64 {
+        self.pushed.count() / self.capacity()
+    }
+
+    /// Sequence number of the oldest pushed element still in the ring, or of
+    /// the next push if nothing was pushed yet.
+    #[inline]
+    pub fn oldest_seq(&self) -> u64 {
+        self.pushed.back(self.numbered as u64)
+    }
+
+    /// Sequence number the next pushed element will get.
+    #[inline]
+    pub fn next_seq(&self) -> u64 {
+        self.pushed.count()
+    }
+
+    /// Logical index of the element with sequence number `seq`, if it is still
+    /// in the ring.
+    #[inline]
+    pub fn seq_to_index(&self, seq: u64) -> Option<usize> {
+        let age = self.pushed.distance(seq);
+        if age == 0 || age > self.numbered as u64 {
+            return None;
+        }
+        Some(self.ring.len() - age as usize)
+    }
+
+    /// Sequence number of the element at logical `index`, if it was pushed.
+    #[inline]
+    pub fn index_to_seq(&self, index: usize) -> Option<u64> {
+        let age = self.ring.len().checked_sub(index)?;
+        if age == 0 || age > self.numbered {
+            return None;
+        }
+        Some(self.pushed.back(age as u64))
+    }
+
+    #[inline]
+    pub fn get_by_seq(&self, seq: u64) -> Option<&S::Element> {
+        self.seq_to_index(seq).map(|index| self.ring.get(index))
+    }
+
+    /// The element `ticket` was issued for, unless it was overwritten.
+    #[inline]
+    pub fn get_by_ticket(&self, ticket: Ticket) -> Option<&S::Element> {
+        self.get_by_seq(ticket.0)
+    }
+
+    /// Remembers the current push position.
+    #[inline]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.pushed.count())
+    }
+
+    /// Iterates over the elements pushed after `snapshot` was taken, oldest
+    /// first.
+    ///
+    /// Fails if some of those elements have already been overwritten; the
+    /// error tells how many were missed.
+    #[allow(clippy::type_complexity)]
+    pub fn iter_since(
+        &self,
+        snapshot: Snapshot,
+    ) -> Result<Skip<Take<Skip<Cycle<slice::Iter<'_, S::Element>>>>>, SnapshotLagError> {
+        let new = self.pushed.distance(snapshot.0);
+        if new > self.numbered as u64 {
+            return Err(SnapshotLagError {
+                missed: new - self.numbered as u64,
+            });
+        }
+        Ok(self.ring.iter().skip(self.ring.len() - new as usize))
+    }
+
+    #[inline]
+    fn capacity(&self) -> u64 {
+        self.ring.len() as u64
+    }
+
+    #[inline]
+    fn count_push(&mut self) {
+        self.count_pushes(1);
+    }
+
+    #[inline]
+    fn count_pushes(&mut self, n: usize) {
+        if !self.try_count_pushes(n) {
+            panic!("sequence counter overflowed");
+        }
+    }
+
+    #[inline]
+    fn try_count_pushes(&mut self, n: usize) -> bool {
+        if !self.pushed.advance(n as u64) {
+            return false;
+        }
+        self.numbered = self.numbered.saturating_add(n).min(self.ring.len());
+        true
+    }
+}
+
+/// Opaque push position of a `Counted` ring, see `Counted::snapshot`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snapshot(u64);
+
+impl Snapshot {
+    /// Sequence number of the first element pushed after the snapshot.
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Handle to an element pushed with `Counted::push_ticket`, for looking it
+/// up later while it is still in the ring.
+///
+/// With a wrapping counter, a ticket kept for a whole wrap of the counter
+/// refers to a newer element.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ticket(u64);
+
+impl Ticket {
+    /// Sequence number of the element.
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Elements pushed after a snapshot were overwritten before being read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SnapshotLagError {
+    /// Number of elements that can no longer be read.
+    pub missed: u64,
+}
+
+impl fmt::Display for SnapshotLagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "snapshot lagged behind by {} elements", self.missed)
+    }
+}
+
+/// A saturated sequence counter refused a push; the element is given back.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SeqOverflow<T>(pub T);
+
+impl<T> fmt::Display for SeqOverflow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sequence counter overflowed")
+    }
+}
+
+impl<S, C> Counted<S, C>
+where
+    S: SliceMut,
+    C: SeqCounter,
+{
+    /// # Panics
+    ///
+    /// Panics if the counter is saturated.
+    #[inline]
+    pub fn push(&mut self, item: S::Element) -> S::Element {
+        self.count_push();
+        self.ring.push(item)
+    }
+
+    /// Like `push`, but fails instead of panicking if the counter is
+    /// saturated.
+    #[inline]
+    pub fn try_push(&mut self, item: S::Element) -> Result<S::Element, SeqOverflow<S::Element>> {
+        if !self.try_count_pushes(1) {
+            return Err(SeqOverflow(item));
+        }
+        Ok(self.ring.push(item))
+    }
+
+    /// Like `push`, but drops the evicted element in place.
+    #[inline]
+    pub fn push_overwrite(&mut self, item: S::Element) {
+        self.count_push();
+        self.ring.push_overwrite(item);
+    }
+
+    /// Like `push_overwrite`, returning a ticket to look the item up with.
+    #[inline]
+    pub fn push_ticket(&mut self, item: S::Element) -> Ticket {
+        let ticket = Ticket(self.next_seq());
+        self.push_overwrite(item);
+        ticket
+    }
+
+    /// Like `Fixed::push_coalesce`. A merged item does not take a sequence
+    /// number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item is not merged and the counter is saturated.
+    #[inline]
+    pub fn push_coalesce<F>(&mut self, item: S::Element, merge: F) -> bool
+    where
+        F: FnOnce(&mut S::Element, &S::Element) -> bool,
+    {
+        match self.try_push_coalesce(item, merge) {
+            Ok(merged) => merged,
+            Err(_) => panic!("sequence counter overflowed"),
+        }
+    }
+
+    /// Like `push_coalesce`, but fails instead of panicking if the item is
+    /// not merged and the counter is saturated. The ring is left unchanged
+    /// then.
+    pub fn try_push_coalesce<F>(
+        &mut self,
+        item: S::Element,
+        merge: F,
+    ) -> Result<bool, SeqOverflow<S::Element>>
+    where
+        F: FnOnce(&mut S::Element, &S::Element) -> bool,
+    {
+        if merge(self.ring.get_back_mut(0), &item) {
+            return Ok(true);
+        }
+        if !self.try_count_pushes(1) {
+            return Err(SeqOverflow(item));
+        }
+        self.ring.push_overwrite(item);
+        Ok(false)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut S::Element {
+        self.ring.get_mut(index)
+    }
+}
+
+impl<S, C> Deref for Counted<S, C> {
+    type Target = Fixed<S>;
+
+    #[inline]
+    fn deref(&self) -> &Fixed<S> {
+        &self.ring
+    }
+}
+
+impl<S> From<Fixed<S>> for Counted<S>
+where
+    S: Slice,
+{
+    #[inline]
+    fn from(ring: Fixed<S>) -> Self {
+        Self::new(ring)
+    }
+}
+
+impl<S> From<S> for Counted<S>
+where
+    S: Slice,
+{
+    #[inline]
+    fn from(data: S) -> Self {
+        Self::new(Fixed::from(data))
+    }
+}
+
+impl<S, C> Extend<S::Element> for Counted<S, C>
+where
+    S: SliceMut,
+    C: SeqCounter,
+{
+    /// Elements known from the size hint to be overwritten by later ones are
+    /// skipped; they still take sequence numbers.
+    fn extend<T: IntoIterator<Item = S::Element>>(&mut self, iter: T) {
+        let mut iter = iter.into_iter();
+        let skipped = self.ring.skip_overwritten(&mut iter);
+        self.count_pushes(skipped);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let mut rb = Counted::from([0u32; 4]);
+        assert_eq!(rb.total_pushed(), 0);
+        assert_eq!(rb.lap_count(), 0);
+        rb.extend(1..=3);
+        assert_eq!(rb.overwritten(), 0);
+        rb.extend(4..=10);
+        assert_eq!(rb.total_pushed(), 10);
+        assert_eq!(rb.overwritten(), 6);
+        assert_eq!(rb.lap_count(), 2);
+        assert_eq!(rb.oldest_seq(), 6);
+        rb.push_overwrite(11);
+        assert_eq!(rb.total_pushed(), 11);
+        assert_eq!(*rb.get_back(0), 11);
+
+        let same = |newest: &mut u32, item: &u32| newest == item;
+        assert!(rb.push_coalesce(11, same));
+        assert!(!rb.push_coalesce(12, same));
+        assert_eq!(rb.total_pushed(), 12);
+    }
+
+    #[test]
+    fn test_iter_since() {
+        let mut rb = Counted::from([0u32; 4]);
+        let start = rb.snapshot();
+        assert_eq!(rb.iter_since(start).unwrap().count(), 0);
+        rb.extend([1, 2]);
+        let mid = rb.snapshot();
+        rb.extend([3, 4, 5]);
+        assert_eq!(
+            rb.iter_since(start).unwrap_err(),
+            SnapshotLagError { missed: 1 }
+        );
+        let new: Vec<u32> = rb.iter_since(mid).unwrap().copied().collect();
+        assert_eq!(new, [3, 4, 5]);
+        let now = rb.snapshot();
+        assert_eq!(now.seq(), 5);
+        assert_eq!(rb.iter_since(now).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_seq_translation() {
+        let mut rb = Counted::from(vec![0u32; 3]);
+        rb.push(10);
+        rb.push(11);
+        assert_eq!(rb.index_to_seq(0), None);
+        assert_eq!(rb.index_to_seq(1), Some(0));
+        assert_eq!(rb.index_to_seq(2), Some(1));
+        assert_eq!(rb.seq_to_index(1), Some(2));
+        assert_eq!(rb.seq_to_index(2), None);
+        rb.extend([12, 13, 14]);
+        assert_eq!(rb.seq_to_index(1), None);
+        assert_eq!(rb.get_by_seq(2), Some(&12));
+        assert_eq!(rb.index_to_seq(0), Some(2));
+        assert_eq!(rb[2], 14);
+    }
+
+    #[test]
+    fn test_saturating_counter() {
+        let mut rb: Counted<_, u32> = Counted::with_counter(Fixed::from([0u8; 2]));
+        rb.pushed = u32::MAX - 1;
+        assert_eq!(rb.try_push(1), Ok(0));
+        assert_eq!(rb.next_seq(), u32::MAX as u64);
+        assert_eq!(rb.try_push(2), Err(SeqOverflow(2)));
+        assert_eq!(rb.get_by_seq(u32::MAX as u64 - 1), Some(&1));
+        assert_eq!(rb.oldest_seq(), u32::MAX as u64 - 1);
+
+        let same = |newest: &mut u8, item: &u8| newest == item;
+        assert_eq!(rb.try_push_coalesce(1, same), Ok(true));
+        assert_eq!(rb.try_push_coalesce(3, same), Err(SeqOverflow(3)));
+        assert!(rb.iter().eq(&[0, 1]));
+        assert_eq!(rb.seq_to_index(u32::MAX as u64 - 1), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "sequence counter overflowed")]
+    fn test_saturated_push_panics() {
+        let mut rb: Counted<_, u32> = Counted::with_counter(Fixed::from([0u8; 2]));
+        rb.pushed = u32::MAX;
+        rb.push(1);
+    }
+
+    #[test]
+    fn test_wrapping_counter() {
+        let mut rb: Counted<_, Wrapping<u32>> = Counted::with_counter(Fixed::from([0u8; 3]));
+        rb.pushed = Wrapping(u32::MAX - 1);
+        let before = rb.snapshot();
+        rb.extend([1, 2, 3]);
+        assert_eq!(rb.next_seq(), 1);
+        assert_eq!(rb.oldest_seq(), u32::MAX as u64 - 1);
+        assert_eq!(rb.get_by_seq(u32::MAX as u64), Some(&2));
+        assert_eq!(rb.get_by_seq(0), Some(&3));
+        assert_eq!(rb.get_by_seq(1), None);
+        assert_eq!(rb.get_by_seq(1 << 32), None);
+        assert_eq!(rb.index_to_seq(0), Some(u32::MAX as u64 - 1));
+        assert_eq!(rb.seq_to_index(0), Some(2));
+        assert!(rb.iter_since(before).unwrap().eq(&[1, 2, 3]));
+
+        rb.push(4);
+        assert_eq!(
+            rb.iter_since(before).unwrap_err(),
+            SnapshotLagError { missed: 1 }
+        );
+    }
+
+    #[test]
+    fn test_extend_skips_overwritten() {
+        let mut rb = Counted::from([0u64; 3]);
+        rb.push(7);
+        let before = rb.snapshot();
+        rb.extend(0..1_000_000_000);
+        assert_eq!(rb.total_pushed(), 1_000_000_001);
+        assert!(rb.iter().eq(&[999_999_997, 999_999_998, 999_999_999]));
+        assert_eq!(rb.get_by_seq(1_000_000_000), Some(&999_999_999));
+        assert_eq!(
+            rb.iter_since(before).unwrap_err(),
+            SnapshotLagError {
+                missed: 999_999_997
+            }
+        );
+    }
+
+    #[test]
+    fn test_tickets() {
+        let mut rb = Counted::from([""; 2]);
+        let first = rb.push_ticket("connected");
+        let second = rb.push_ticket("request");
+        assert_eq!(second.seq(), 1);
+        assert_eq!(rb.get_by_ticket(first), Some(&"connected"));
+        rb.push("response");
+        assert_eq!(rb.get_by_ticket(first), None);
+        assert_eq!(rb.get_by_ticket(second), Some(&"request"));
+    }
+}
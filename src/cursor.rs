@@ -0,0 +1,208 @@
+//! Read position over a ring, for parsers that need to look ahead and
+//! backtrack.
+
+use core::mem;
+
+use super::fixed::Fixed;
+use super::traits::Slice;
+
+/// Logical read position over a ring.
+///
+/// Position `len` is the end of the ring. A cursor is `Copy`, so saving and
+/// restoring it is enough to backtrack.
+#[derive(Debug)]
+pub struct Cursor<'a, S> {
+    ring: &'a Fixed<S>,
+    pos: usize,
+}
+
+impl<S> Clone for Cursor<'_, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Cursor<'_, S> {}
+
+impl<'a, S> Cursor<'a, S>
+where
+    S: Slice,
+{
+    /// Cursor at the oldest element of `ring`.
+    #[inline]
+    pub fn new(ring: &'a Fixed<S>) -> Self {
+        Cursor { ring, pos: 0 }
+    }
+
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of elements between the cursor and the end of the ring.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.ring.len() - self.pos
+    }
+
+    /// Moves to logical `index`. Panics if `index` is past the end.
+    #[inline]
+    pub fn seek(&mut self, index: usize) {
+        assert!(index <= self.ring.len(), "cursor position out of bounds");
+        self.pos = index;
+    }
+
+    /// Moves `n` elements forward. Panics if that goes past the end.
+    #[inline]
+    pub fn advance(&mut self, n: usize) {
+        assert!(n <= self.remaining(), "cursor position out of bounds");
+        self.pos += n;
+    }
+
+    /// Element under the cursor, without moving.
+    #[inline]
+    pub fn peek(&self) -> Option<&'a S::Element> {
+        self.peek_at(0)
+    }
+
+    /// Element `n` positions after the cursor, without moving.
+    #[inline]
+    pub fn peek_at(&self, n: usize) -> Option<&'a S::Element> {
+        let ring: &'a Fixed<S> = self.ring;
+        (n < self.remaining()).then(|| ring.get(self.pos + n))
+    }
+}
+
+impl<'a, S> Iterator for Cursor<'a, S>
+where
+    S: Slice,
+{
+    type Item = &'a S::Element;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.peek()?;
+        self.pos += 1;
+        Some(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl<S> ExactSizeIterator for Cursor<'_, S> where S: Slice {}
+
+macro_rules! read_methods {
+    ($($t:ty => $le:ident, $be:ident;)*) => {
+        $(
+            #[inline]
+            pub fn $le(&mut self) -> Option<$t> {
+                let mut bytes = [0; mem::size_of::<$t>()];
+                self.read_bytes(&mut bytes).then(|| <$t>::from_le_bytes(bytes))
+            }
+
+            #[inline]
+            pub fn $be(&mut self) -> Option<$t> {
+                let mut bytes = [0; mem::size_of::<$t>()];
+                self.read_bytes(&mut bytes).then(|| <$t>::from_be_bytes(bytes))
+            }
+        )*
+    };
+}
+
+/// Typed reads from byte rings. Each read returns `None` and leaves the
+/// cursor in place if too few bytes remain.
+impl<S> Cursor<'_, S>
+where
+    S: Slice<Element = u8>,
+{
+    /// Fills `dst` from the cursor and moves past the bytes read.
+    pub fn read_bytes(&mut self, dst: &mut [u8]) -> bool {
+        if dst.len() > self.remaining() {
+            return false;
+        }
+        self.ring.copy_out(self.pos, dst);
+        self.pos += dst.len();
+        true
+    }
+
+    #[inline]
+    pub fn read_u8(&mut self) -> Option<u8> {
+        self.next().copied()
+    }
+
+    #[inline]
+    pub fn read_i8(&mut self) -> Option<i8> {
+        self.read_u8().map(|byte| byte as i8)
+    }
+
+    read_methods! {
+        u16 => read_u16_le, read_u16_be;
+        u32 => read_u32_le, read_u32_be;
+        u64 => read_u64_le, read_u64_be;
+        i16 => read_i16_le, read_i16_be;
+        i32 => read_i32_le, read_i32_be;
+        i64 => read_i64_le, read_i64_be;
+        f32 => read_f32_le, read_f32_be;
+        f64 => read_f64_le, read_f64_be;
+    }
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice,
+{
+    /// Cursor at the oldest element.
+    #[inline]
+    pub fn cursor(&self) -> Cursor<'_, S> {
+        Cursor::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_seek_and_peek() {
+        let mut rb = Fixed::from([0; 4]);
+        for i in 1..=6 {
+            rb.push(i);
+        }
+        let mut cursor = rb.cursor();
+        assert_eq!(cursor.peek(), Some(&3));
+        cursor.advance(2);
+        assert_eq!(cursor.peek_at(1), Some(&6));
+        let saved = cursor;
+        assert_eq!(cursor.by_ref().copied().collect::<Vec<_>>(), [5, 6]);
+        assert_eq!(cursor.peek(), None);
+        cursor = saved;
+        assert_eq!(cursor.position(), 2);
+        cursor.seek(0);
+        assert_eq!(cursor.len(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_advance_past_end() {
+        Fixed::from([0; 2]).cursor().advance(3);
+    }
+
+    #[test]
+    fn test_typed_reads() {
+        let mut rb = Fixed::from([0u8; 8]);
+        for byte in [0xaa, 0xbb, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08] {
+            rb.push(byte);
+        }
+        let mut cursor = rb.cursor();
+        cursor.advance(1);
+        assert_eq!(cursor.read_u16_be(), Some(0x0203));
+        assert_eq!(cursor.read_u32_le(), Some(0x0706_0504));
+        assert_eq!(cursor.read_u16_le(), None);
+        assert_eq!(cursor.position(), 7);
+        assert_eq!(cursor.read_i8(), Some(8));
+    }
+}
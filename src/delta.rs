@@ -0,0 +1,276 @@
+//! Compact snapshots of integer rings.
+//!
+//! Each element is stored as the difference to its predecessor (the first one
+//! to zero), zigzag-mapped so small negative steps stay small, and written as
+//! a LEB128 varint. Slowly changing counters shrink to about a byte per
+//! element. All fixed-size integers are little-endian:
+//!
+//! | offset | size | field                              |
+//! |--------|------|------------------------------------|
+//! | 0      | 4    | magic `b"RBDZ"`                    |
+//! | 4      | 1    | format version (currently 1)       |
+//! | 5      | 3    | reserved, zero                     |
+//! | 8      | 4    | capacity of the encoded ring       |
+//! | 12     | 4    | number of elements                 |
+//! | 16     | ..   | elements: zigzag varint deltas     |
+
+use core::marker::PhantomData;
+
+use super::dump::{DecodeError, Header};
+use super::fixed::Fixed;
+use super::traits::Slice;
+use super::Vec;
+
+pub const MAGIC: [u8; 4] = *b"RBDZ";
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = super::dump::HEADER_LEN;
+/// Longest varint encoding of a `u64`.
+pub const MAX_VARINT_LEN: usize = 10;
+
+/// Integer that can be delta encoded.
+///
+/// Values are widened to 64 bits (sign-extended for signed types); deltas wrap,
+/// so every value round-trips exactly.
+pub trait DeltaInt: Copy {
+    fn to_bits(self) -> u64;
+    fn from_bits(bits: u64) -> Self;
+}
+
+macro_rules! impl_delta_int {
+    ($($t:ty => $wide:ty),*) => {
+        $(impl DeltaInt for $t {
+            #[inline]
+            fn to_bits(self) -> u64 {
+                self as $wide as u64
+            }
+
+            #[inline]
+            fn from_bits(bits: u64) -> Self {
+                bits as $t
+            }
+        })*
+    };
+}
+
+impl_delta_int!(
+    u8 => u64, u16 => u64, u32 => u64, u64 => u64, usize => u64,
+    i8 => i64, i16 => i64, i32 => i64, i64 => i64, isize => i64
+);
+
+#[inline]
+fn zigzag(delta: u64) -> u64 {
+    let delta = delta as i64;
+    ((delta << 1) ^ (delta >> 63)) as u64
+}
+
+#[inline]
+fn unzigzag(value: u64) -> u64 {
+    (value >> 1) ^ (value & 1).wrapping_neg()
+}
+
+/// Writes `value` as a LEB128 varint into `out`, returning the bytes used.
+#[inline]
+fn write_varint(mut value: u64, out: &mut [u8; MAX_VARINT_LEN]) -> usize {
+    let mut len = 0;
+    while value >= 0x80 {
+        out[len] = value as u8 | 0x80;
+        value >>= 7;
+        len += 1;
+    }
+    out[len] = value as u8;
+    len + 1
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice,
+    S::Element: DeltaInt,
+{
+    /// Writes the ring in the delta format described in the module docs.
+    /// Fails with `InvalidInput`, before writing anything, if the capacity
+    /// does not fit in a `u32`.
+    #[cfg(feature = "std")]
+    pub fn encode_delta<W>(&self, out: &mut W) -> std::io::Result<()>
+    where
+        W: std::io::Write + ?Sized,
+    {
+        let header = self
+            .delta_header()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, TOO_LARGE))?;
+        out.write_all(&header)?;
+        let mut buf = [0; MAX_VARINT_LEN];
+        for value in self.deltas() {
+            let len = write_varint(value, &mut buf);
+            out.write_all(&buf[..len])?;
+        }
+        Ok(())
+    }
+
+    /// Appends the ring in the delta format to `out`. Panics if the capacity
+    /// does not fit in a `u32`.
+    pub fn encode_delta_to_vec(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.delta_header().expect(TOO_LARGE));
+        let mut buf = [0; MAX_VARINT_LEN];
+        for value in self.deltas() {
+            let len = write_varint(value, &mut buf);
+            out.extend_from_slice(&buf[..len]);
+        }
+    }
+
+    #[inline]
+    fn delta_header(&self) -> Option<[u8; HEADER_LEN]> {
+        Header::encode(MAGIC, VERSION, self.len(), self.len())
+    }
+
+    fn deltas(&self) -> impl Iterator<Item = u64> + '_ {
+        let mut prev = 0u64;
+        self.iter().map(move |value| {
+            let bits = value.to_bits();
+            let delta = bits.wrapping_sub(prev);
+            prev = bits;
+            zigzag(delta)
+        })
+    }
+}
+
+const TOO_LARGE: &str = "ring too large for a delta snapshot";
+
+/// Decodes the elements of a delta snapshot, oldest first, one at a time.
+#[derive(Clone, Debug)]
+pub struct DeltaDecoder<'a, T> {
+    rest: &'a [u8],
+    capacity: usize,
+    remaining: usize,
+    prev: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T> DeltaDecoder<'a, T>
+where
+    T: DeltaInt,
+{
+    pub fn new(data: &'a [u8]) -> Result<Self, DecodeError> {
+        let header = Header::decode(data, MAGIC, VERSION)?;
+        Ok(DeltaDecoder {
+            rest: &data[HEADER_LEN..],
+            capacity: header.capacity,
+            remaining: header.count,
+            prev: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Capacity of the ring the snapshot was taken from.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of elements not yet decoded.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn read_varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for (i, &byte) in self.rest.iter().enumerate() {
+            if i == MAX_VARINT_LEN || (i == MAX_VARINT_LEN - 1 && byte > 1) {
+                return Err(DecodeError::Malformed);
+            }
+            value |= u64::from(byte & 0x7f) << (7 * i);
+            if byte & 0x80 == 0 {
+                self.rest = &self.rest[i + 1..];
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::Truncated)
+    }
+}
+
+impl<T> Iterator for DeltaDecoder<'_, T>
+where
+    T: DeltaInt,
+{
+    type Item = Result<T, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match self.read_varint() {
+            Ok(value) => {
+                self.remaining -= 1;
+                self.prev = self.prev.wrapping_add(unzigzag(value));
+                Some(Ok(T::from_bits(self.prev)))
+            }
+            Err(err) => {
+                self.remaining = 0;
+                Some(Err(err))
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut rb = Fixed::from([0i64; 5]);
+        for value in [i64::MIN, -3, 4, i64::MAX, 0, 1000, 1001] {
+            rb.push(value);
+        }
+        let mut out = Vec::new();
+        rb.encode_delta(&mut out).unwrap();
+
+        let decoder = DeltaDecoder::<i64>::new(&out).unwrap();
+        assert_eq!(decoder.capacity(), 5);
+        assert_eq!(decoder.remaining(), 5);
+        let values: Result<Vec<_>, _> = decoder.collect();
+        assert_eq!(values.unwrap(), [4, i64::MAX, 0, 1000, 1001]);
+
+        let mut again = Vec::new();
+        rb.encode_delta_to_vec(&mut again);
+        assert_eq!(out, again);
+    }
+
+    #[test]
+    fn test_slow_counter_is_compact() {
+        let mut rb = Fixed::from(vec![0u64; 1000]);
+        for i in 0..1000u64 {
+            rb.push(1_700_000_000 + i * 3);
+        }
+        let mut out = Vec::new();
+        rb.encode_delta_to_vec(&mut out);
+        assert!(out.len() < HEADER_LEN + 1000 + MAX_VARINT_LEN);
+        assert!(DeltaDecoder::<u64>::new(&out)
+            .unwrap()
+            .map(Result::unwrap)
+            .eq(rb.iter().copied()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            DeltaDecoder::<u8>::new(b"RBDP").unwrap_err(),
+            DecodeError::BadMagic
+        );
+        let mut out = Vec::new();
+        Fixed::from([200u8, 1]).encode_delta_to_vec(&mut out);
+        out.pop();
+        let values: Vec<_> = DeltaDecoder::<u8>::new(&out).unwrap().collect();
+        assert_eq!(values, [Ok(200), Err(DecodeError::Truncated)]);
+
+        let mut bad = Header::encode(MAGIC, VERSION, 1, 1).unwrap().to_vec();
+        bad.extend_from_slice(&[0xff; MAX_VARINT_LEN]);
+        let values: Vec<_> = DeltaDecoder::<u64>::new(&bad).unwrap().collect();
+        assert_eq!(values, [Err(DecodeError::Malformed)]);
+    }
+}
@@ -0,0 +1,344 @@
+//! Versioned binary dump format for rings of byte records.
+//!
+//! A dump is a fixed header followed by the records, oldest first. All integers
+//! are little-endian:
+//!
+//! | offset | size | field                              |
+//! |--------|------|------------------------------------|
+//! | 0      | 4    | magic `b"RBDP"`                    |
+//! | 4      | 1    | format version (currently 1)       |
+//! | 5      | 3    | reserved, zero                     |
+//! | 8      | 4    | capacity of the dumped ring        |
+//! | 12     | 4    | number of records                  |
+//! | 16     | ..   | records: `u32` length, then bytes  |
+
+use core::fmt;
+
+use super::fixed::Fixed;
+use super::traits::Slice;
+use super::{Box, Vec};
+
+pub const MAGIC: [u8; 4] = *b"RBDP";
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 16;
+
+/// Element that can be written as a record of a binary dump.
+///
+/// Elements returning `None` (e.g. empty `Option` slots) are left out.
+pub trait DumpRecord {
+    fn as_record(&self) -> Option<&[u8]>;
+}
+
+impl DumpRecord for [u8] {
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl DumpRecord for str {
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        Some(self.as_bytes())
+    }
+}
+
+impl<const N: usize> DumpRecord for [u8; N] {
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl DumpRecord for Vec<u8> {
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl DumpRecord for Box<[u8]> {
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl DumpRecord for alloc::string::String {
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        Some(self.as_bytes())
+    }
+}
+
+impl<T> DumpRecord for &T
+where
+    T: DumpRecord + ?Sized,
+{
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        (**self).as_record()
+    }
+}
+
+impl<T> DumpRecord for Option<T>
+where
+    T: DumpRecord,
+{
+    #[inline]
+    fn as_record(&self) -> Option<&[u8]> {
+        self.as_ref().and_then(T::as_record)
+    }
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice,
+    S::Element: DumpRecord,
+{
+    /// Writes the ring in the binary dump format described in the module docs.
+    /// Fails with `InvalidInput`, before writing anything, if the capacity, a
+    /// record or the number of records does not fit in a `u32`.
+    #[cfg(feature = "std")]
+    pub fn dump_binary<W>(&self, out: &mut W) -> std::io::Result<()>
+    where
+        W: std::io::Write + ?Sized,
+    {
+        let header = self
+            .dump_header()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, TOO_LARGE))?;
+        out.write_all(&header)?;
+        for record in self.iter().filter_map(DumpRecord::as_record) {
+            out.write_all(&(record.len() as u32).to_le_bytes())?;
+            out.write_all(record)?;
+        }
+        Ok(())
+    }
+
+    /// Appends the ring in the binary dump format to `out`. Panics if the
+    /// capacity, a record or the number of records does not fit in a `u32`.
+    pub fn dump_binary_to_vec(&self, out: &mut Vec<u8>) {
+        let header = self.dump_header().expect(TOO_LARGE);
+        out.extend_from_slice(&header);
+        for record in self.iter().filter_map(DumpRecord::as_record) {
+            out.extend_from_slice(&(record.len() as u32).to_le_bytes());
+            out.extend_from_slice(record);
+        }
+    }
+
+    /// Header of the dump, or `None` if the format cannot hold the records.
+    fn dump_header(&self) -> Option<[u8; HEADER_LEN]> {
+        let mut count = 0;
+        for record in self.iter().filter_map(DumpRecord::as_record) {
+            if record.len() > u32::MAX as usize {
+                return None;
+            }
+            count += 1;
+        }
+        Header::encode(MAGIC, VERSION, self.len(), count)
+    }
+}
+
+const TOO_LARGE: &str = "ring too large for a ring dump";
+
+/// Header shared by the dump and delta formats: magic, version, three
+/// reserved bytes, then the capacity and the number of entries as `u32`s.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Header {
+    pub(crate) version: u8,
+    pub(crate) capacity: usize,
+    pub(crate) count: usize,
+}
+
+impl Header {
+    /// Encodes a header, or returns `None` if `capacity` or `count` does not
+    /// fit in a `u32`.
+    pub(crate) fn encode(
+        magic: [u8; 4],
+        version: u8,
+        capacity: usize,
+        count: usize,
+    ) -> Option<[u8; HEADER_LEN]> {
+        let mut header = [0; HEADER_LEN];
+        header[..4].copy_from_slice(&magic);
+        header[4] = version;
+        header[8..12].copy_from_slice(&u32::try_from(capacity).ok()?.to_le_bytes());
+        header[12..16].copy_from_slice(&u32::try_from(count).ok()?.to_le_bytes());
+        Some(header)
+    }
+
+    /// Decodes the header at the start of `data`, accepting versions from 1
+    /// up to `max_version`.
+    pub(crate) fn decode(
+        data: &[u8],
+        magic: [u8; 4],
+        max_version: u8,
+    ) -> Result<Self, DecodeError> {
+        if data.len() < 4 || data[..4] != magic {
+            return Err(DecodeError::BadMagic);
+        }
+        if data.len() < HEADER_LEN {
+            return Err(DecodeError::Truncated);
+        }
+        let version = data[4];
+        if version == 0 || version > max_version {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        Ok(Header {
+            version,
+            capacity: read_u32(&data[8..]) as usize,
+            count: read_u32(&data[12..]) as usize,
+        })
+    }
+}
+
+/// Failure to decode a binary dump.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DecodeError {
+    /// The data does not start with the dump magic.
+    BadMagic,
+    /// The dump was written by a newer, unknown format version.
+    UnsupportedVersion(u8),
+    /// The data ends in the middle of the header or a record.
+    Truncated,
+    /// A record is encoded in a way no encoder produces.
+    Malformed,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::BadMagic => f.write_str("not a ring dump"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported ring dump version {}", version)
+            }
+            DecodeError::Truncated => f.write_str("truncated ring dump"),
+            DecodeError::Malformed => f.write_str("malformed ring dump"),
+        }
+    }
+}
+
+/// Reads the records of a binary dump, oldest first, without copying them.
+#[derive(Clone, Debug)]
+pub struct Decoder<'a> {
+    rest: &'a [u8],
+    version: u8,
+    capacity: usize,
+    remaining: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self, DecodeError> {
+        let header = Header::decode(data, MAGIC, VERSION)?;
+        Ok(Decoder {
+            rest: &data[HEADER_LEN..],
+            version: header.version,
+            capacity: header.capacity,
+            remaining: header.count,
+        })
+    }
+
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Capacity of the ring the dump was taken from.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of records not yet decoded.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn truncated(&mut self) -> Result<&'a [u8], DecodeError> {
+        self.remaining = 0;
+        Err(DecodeError::Truncated)
+    }
+}
+
+impl<'a> Iterator for Decoder<'a> {
+    type Item = Result<&'a [u8], DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let len = match self.rest.get(..4) {
+            Some(len) => read_u32(len) as usize,
+            None => return Some(self.truncated()),
+        };
+        // Only overflows where `usize` is 32 bits or narrower.
+        let end = match len.checked_add(4) {
+            Some(end) => end,
+            None => {
+                self.remaining = 0;
+                return Some(Err(DecodeError::Malformed));
+            }
+        };
+        match self.rest.get(4..end) {
+            Some(record) => {
+                self.rest = &self.rest[end..];
+                Some(Ok(record))
+            }
+            None => Some(self.truncated()),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+#[inline]
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut rb = Fixed::from(vec![None; 3]);
+        rb.push(Some(String::from("boot")));
+        rb.push(Some(String::from("")));
+        let mut out = Vec::new();
+        rb.dump_binary(&mut out).unwrap();
+
+        let mut decoder = Decoder::new(&out).unwrap();
+        assert_eq!(decoder.capacity(), 3);
+        assert_eq!(decoder.remaining(), 2);
+        assert_eq!(decoder.next(), Some(Ok(&b"boot"[..])));
+        assert_eq!(decoder.next(), Some(Ok(&b""[..])));
+        assert_eq!(decoder.next(), None);
+
+        let mut again = Vec::new();
+        rb.dump_binary_to_vec(&mut again);
+        assert_eq!(out, again);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Decoder::new(b"nope").unwrap_err(), DecodeError::BadMagic);
+        let mut out = Vec::new();
+        Fixed::from([[1u8, 2]; 2]).dump_binary_to_vec(&mut out);
+        out[4] = 9;
+        assert_eq!(
+            Decoder::new(&out).unwrap_err(),
+            DecodeError::UnsupportedVersion(9)
+        );
+        out[4] = VERSION;
+        out.pop();
+        let records: Vec<_> = Decoder::new(&out).unwrap().collect();
+        assert_eq!(records, [Ok(&[1u8, 2][..]), Err(DecodeError::Truncated)]);
+    }
+}
@@ -0,0 +1,54 @@
+//! The error types of the crate, in one place.
+//!
+//! Each error is defined next to the API that returns it and re-exported
+//! here. All of them implement `core::error::Error`, in `no_std` builds as
+//! well, so they work with `?` and with error-handling crates.
+
+use core::error::Error;
+use core::fmt;
+
+pub use super::combine::LengthMismatch;
+pub use super::counted::{SeqOverflow, SnapshotLagError};
+pub use super::dump::DecodeError;
+pub use super::fixed::{IndexError, TooFewElements};
+#[cfg(feature = "std")]
+pub use super::latest::Closed;
+pub use super::queue::QueueFull;
+pub use super::record_ring::RecordTooLarge;
+
+impl Error for DecodeError {}
+impl Error for IndexError {}
+impl Error for LengthMismatch {}
+impl Error for RecordTooLarge {}
+impl Error for SnapshotLagError {}
+impl Error for TooFewElements {}
+
+#[cfg(feature = "std")]
+impl Error for Closed {}
+
+impl<T> Error for QueueFull<T> where T: fmt::Debug {}
+impl<T> Error for SeqOverflow<T> where T: fmt::Debug {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixed::Fixed;
+    use crate::record_ring::RecordRing;
+
+    fn push_and_read(ring: &mut RecordRing<[u8; 8]>) -> Result<u32, Box<dyn Error>> {
+        ring.push_record(b"abc")?;
+        let physical = Fixed::from([0; 4]).logical_to_physical(4)?;
+        Ok(physical as u32)
+    }
+
+    #[test]
+    fn test_question_mark() {
+        let mut ring = RecordRing::new([0; 8]);
+        let err = push_and_read(&mut ring).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "index 4 out of bounds for ring of length 4"
+        );
+        assert!(err.downcast_ref::<IndexError>().is_some());
+    }
+}
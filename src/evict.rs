@@ -0,0 +1,131 @@
+use core::ops::Deref;
+
+use super::fixed::Fixed;
+use super::traits::{Slice, SliceMut};
+
+/// Receives every element pushed out of an `Evicting` ring.
+///
+/// Implemented for any `FnMut(T)`.
+pub trait EvictHook<T> {
+    fn on_evict(&mut self, item: T);
+}
+
+impl<T, F> EvictHook<T> for F
+where
+    F: FnMut(T),
+{
+    #[inline]
+    fn on_evict(&mut self, item: T) {
+        self(item)
+    }
+}
+
+/// `Fixed` ring that hands each overwritten element to a hook.
+///
+/// This keeps resource cleanup for evicted elements (closing handles,
+/// returning pooled buffers, logging) in one place instead of at every `push`
+/// call site. Note that the elements the storage was created with are passed
+/// to the hook as well once they are overwritten.
+#[derive(Copy, Clone, Debug)]
+pub struct Evicting<S, H> {
+    ring: Fixed<S>,
+    hook: H,
+}
+
+impl<S, H> Evicting<S, H>
+where
+    S: SliceMut,
+    H: EvictHook<S::Element>,
+{
+    #[inline]
+    pub fn new(ring: Fixed<S>, hook: H) -> Self {
+        Evicting { ring, hook }
+    }
+
+    #[inline]
+    pub fn push(&mut self, item: S::Element) {
+        let old = self.ring.push(item);
+        self.hook.on_evict(old);
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut S::Element {
+        self.ring.get_mut(index)
+    }
+
+    #[inline]
+    pub fn hook(&self) -> &H {
+        &self.hook
+    }
+
+    #[inline]
+    pub fn hook_mut(&mut self) -> &mut H {
+        &mut self.hook
+    }
+
+    /// Returns the ring and the hook without evicting the remaining elements.
+    #[inline]
+    pub fn into_parts(self) -> (Fixed<S>, H) {
+        (self.ring, self.hook)
+    }
+}
+
+impl<S, H> Deref for Evicting<S, H>
+where
+    S: Slice,
+{
+    type Target = Fixed<S>;
+
+    #[inline]
+    fn deref(&self) -> &Fixed<S> {
+        &self.ring
+    }
+}
+
+impl<S, H> Extend<S::Element> for Evicting<S, H>
+where
+    S: SliceMut,
+    H: EvictHook<S::Element>,
+{
+    fn extend<T: IntoIterator<Item = S::Element>>(&mut self, iter: T) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hook_sees_evicted() {
+        let mut evicted = Vec::new();
+        {
+            let mut rb = Evicting::new(Fixed::from([0; 2]), |item| evicted.push(item));
+            rb.extend([1, 2, 3, 4]);
+            assert_eq!(rb[0], 3);
+        }
+        assert_eq!(evicted, [0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_trait_hook() {
+        struct Pool(Vec<Vec<u8>>);
+
+        impl EvictHook<Vec<u8>> for Pool {
+            fn on_evict(&mut self, mut buf: Vec<u8>) {
+                buf.clear();
+                self.0.push(buf);
+            }
+        }
+
+        let mut rb = Evicting::new(Fixed::from(vec![Vec::new()]), Pool(Vec::new()));
+        rb.push(vec![1, 2, 3]);
+        rb.push(vec![4]);
+        let (ring, pool) = rb.into_parts();
+        assert_eq!(ring[0], [4]);
+        assert_eq!(pool.0.len(), 2);
+        assert!(pool.0.iter().all(Vec::is_empty));
+    }
+}
@@ -0,0 +1,141 @@
+//! C API for byte rings.
+//!
+//! Build the crate as a `staticlib` or `cdylib` with the `ffi` feature and
+//! declare:
+//!
+//! ```c
+//! typedef struct RbRing RbRing;
+//!
+//! RbRing *rb_create(size_t capacity);
+//! size_t rb_capacity(const RbRing *rb);
+//! int rb_push_bytes(RbRing *rb, const uint8_t *data, size_t len);
+//! size_t rb_read(const RbRing *rb, uint8_t *out, size_t len);
+//! void rb_free(RbRing *rb);
+//! ```
+//!
+//! Like `Fixed`, the ring is always full: it starts out as `capacity` zero
+//! bytes and every pushed byte evicts the oldest one.
+
+use core::ffi::c_int;
+use core::ptr;
+use core::slice;
+
+use super::fixed::Fixed;
+use super::Box;
+
+/// Opaque ring handle handed out to C.
+pub struct RbRing {
+    inner: Fixed<Box<[u8]>>,
+}
+
+/// Creates a ring of `capacity` bytes. Returns null if `capacity` is zero.
+#[no_mangle]
+pub extern "C" fn rb_create(capacity: usize) -> *mut RbRing {
+    if capacity == 0 {
+        return ptr::null_mut();
+    }
+    let inner = Fixed::from(alloc::vec![0u8; capacity].into_boxed_slice());
+    Box::into_raw(Box::new(RbRing { inner }))
+}
+
+/// Returns the capacity of `rb`, or zero if `rb` is null.
+///
+/// # Safety
+///
+/// `rb` must be null or a live pointer returned by `rb_create`.
+#[no_mangle]
+pub unsafe extern "C" fn rb_capacity(rb: *const RbRing) -> usize {
+    match rb.as_ref() {
+        Some(rb) => rb.inner.len(),
+        None => 0,
+    }
+}
+
+/// Pushes `len` bytes from `data`, evicting the oldest bytes. Returns 0 on
+/// success and -1 if `rb` is null or `data` is null with a non-zero `len`.
+///
+/// # Safety
+///
+/// `rb` must be null or a live pointer returned by `rb_create`, not used
+/// concurrently. `data` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_push_bytes(rb: *mut RbRing, data: *const u8, len: usize) -> c_int {
+    let rb = match rb.as_mut() {
+        Some(rb) => rb,
+        None => return -1,
+    };
+    if len == 0 {
+        return 0;
+    }
+    if data.is_null() {
+        return -1;
+    }
+    rb.inner.extend_from_slice(slice::from_raw_parts(data, len));
+    0
+}
+
+/// Copies up to `len` bytes of `rb`, oldest first, into `out` and returns the
+/// number of bytes copied. Returns 0 if `rb` or `out` is null.
+///
+/// # Safety
+///
+/// `rb` must be null or a live pointer returned by `rb_create`. `out` must be
+/// valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rb_read(rb: *const RbRing, out: *mut u8, len: usize) -> usize {
+    let rb = match rb.as_ref() {
+        Some(rb) if !out.is_null() => rb,
+        _ => return 0,
+    };
+    let copied = len.min(rb.inner.len());
+    rb.inner.copy_out(0, slice::from_raw_parts_mut(out, copied));
+    copied
+}
+
+/// Frees a ring. Does nothing if `rb` is null.
+///
+/// # Safety
+///
+/// `rb` must be null or a pointer returned by `rb_create` that was not freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn rb_free(rb: *mut RbRing) {
+    if !rb.is_null() {
+        drop(Box::from_raw(rb));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        unsafe {
+            assert!(rb_create(0).is_null());
+            let rb = rb_create(4);
+            assert_eq!(rb_capacity(rb), 4);
+            assert_eq!(rb_push_bytes(rb, b"abc".as_ptr(), 3), 0);
+            assert_eq!(rb_push_bytes(rb, b"defghij".as_ptr(), 7), 0);
+            assert_eq!(rb_push_bytes(rb, ptr::null(), 0), 0);
+            assert_eq!(rb_push_bytes(rb, ptr::null(), 1), -1);
+
+            let mut out = [0u8; 8];
+            assert_eq!(rb_read(rb, out.as_mut_ptr(), out.len()), 4);
+            assert_eq!(&out[..4], b"ghij");
+            assert_eq!(rb_read(rb, out.as_mut_ptr(), 2), 2);
+            assert_eq!(&out[..2], b"gh");
+            rb_free(rb);
+        }
+    }
+
+    #[test]
+    fn test_null() {
+        unsafe {
+            assert_eq!(rb_capacity(ptr::null()), 0);
+            assert_eq!(rb_push_bytes(ptr::null_mut(), b"a".as_ptr(), 1), -1);
+            assert_eq!(rb_read(ptr::null(), ptr::null_mut(), 1), 0);
+            rb_free(ptr::null_mut());
+        }
+    }
+}
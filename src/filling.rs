@@ -0,0 +1,181 @@
+//! Type states separating a window that is still filling up from a full one.
+//!
+//! The storage of a `Fixed` always holds `len` elements, but until `len`
+//! items have been pushed some of them are placeholders. `Filling` hides the
+//! placeholders and turns into `Full` with the last push, so APIs that need
+//! exactly `len` real samples can take a `Full` and reject the rest at
+//! compile time.
+
+use core::ops::{Deref, DerefMut};
+
+use super::fixed::Fixed;
+use super::ring_slice::RingSlice;
+use super::traits::{Slice, SliceMut};
+
+/// A window that has not seen `len` pushes yet.
+#[derive(Clone, Debug)]
+pub struct Filling<S> {
+    ring: Fixed<S>,
+    filled: usize,
+}
+
+/// A window whose every element was pushed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Full<S> {
+    ring: Fixed<S>,
+}
+
+/// Result of pushing to a `Filling` window.
+#[derive(Clone, Debug)]
+pub enum Pushed<S> {
+    Filling(Filling<S>),
+    Full(Full<S>),
+}
+
+impl<S> Filling<S>
+where
+    S: Slice,
+{
+    /// An empty window over `storage`, whose current elements are treated as
+    /// placeholders.
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Filling {
+            ring: Fixed::from(storage),
+            filled: 0,
+        }
+    }
+
+    /// Number of elements pushed so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.filled
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.filled == 0
+    }
+
+    /// Number of pushes left until the window is full.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.ring.len() - self.filled
+    }
+
+    /// The elements pushed so far, oldest first.
+    #[inline]
+    pub fn elements(&self) -> RingSlice<'_, S::Element> {
+        self.ring.range(self.remaining()..)
+    }
+}
+
+impl<S> Filling<S>
+where
+    S: SliceMut,
+{
+    /// Pushes `item`, turning into `Full` if it was the last one missing.
+    pub fn push(mut self, item: S::Element) -> Pushed<S> {
+        self.ring.push_overwrite(item);
+        self.filled += 1;
+        if self.filled == self.ring.len() {
+            Pushed::Full(Full { ring: self.ring })
+        } else {
+            Pushed::Filling(self)
+        }
+    }
+}
+
+impl<S> Pushed<S>
+where
+    S: SliceMut,
+{
+    /// Pushes `item` in whichever state the window is in.
+    #[inline]
+    pub fn push(self, item: S::Element) -> Pushed<S> {
+        match self {
+            Pushed::Filling(filling) => filling.push(item),
+            Pushed::Full(mut full) => {
+                full.push_overwrite(item);
+                Pushed::Full(full)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn full(self) -> Option<Full<S>> {
+        match self {
+            Pushed::Filling(_) => None,
+            Pushed::Full(full) => Some(full),
+        }
+    }
+}
+
+impl<S> Full<S> {
+    #[inline]
+    pub fn into_inner(self) -> Fixed<S> {
+        self.ring
+    }
+}
+
+impl<S> From<Fixed<S>> for Full<S> {
+    /// Treats every element of `ring` as a real sample.
+    #[inline]
+    fn from(ring: Fixed<S>) -> Self {
+        Full { ring }
+    }
+}
+
+impl<S> Deref for Full<S> {
+    type Target = Fixed<S>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.ring
+    }
+}
+
+impl<S> DerefMut for Full<S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.ring
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mean(window: &Full<[u32; 3]>) -> u32 {
+        window.iter().sum::<u32>() / window.len() as u32
+    }
+
+    #[test]
+    fn test_filling_to_full() {
+        let mut state = Pushed::Filling(Filling::new([u32::MAX; 3]));
+        for (i, item) in [3, 6].into_iter().enumerate() {
+            state = match state.push(item) {
+                Pushed::Filling(filling) => {
+                    assert_eq!(filling.len(), i + 1);
+                    assert_eq!(filling.remaining(), 2 - i);
+                    assert!(filling.elements().iter().eq(&[3, 6][..=i]));
+                    Pushed::Filling(filling)
+                }
+                Pushed::Full(_) => panic!("full too early"),
+            };
+        }
+        let mut full = state.push(9).full().unwrap();
+        assert_eq!(mean(&full), 6);
+        full.push(12);
+        assert_eq!(mean(&full), 9);
+        assert_eq!(full.into_inner().first(), 1);
+    }
+
+    #[test]
+    fn test_full_stays_full() {
+        let state = Pushed::Full(Full::from(Fixed::from([1, 2])));
+        let full = state.push(3).full().unwrap();
+        assert!(full.iter().eq(&[2, 3]));
+        assert!(Filling::new([0; 2]).elements().is_empty());
+    }
+}
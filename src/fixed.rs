@@ -0,0 +1,1771 @@
+use core::array;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::{Chain, Cycle, FromIterator, Skip, Take};
+use core::mem;
+use core::ops::{Bound, Deref, DerefMut, Index, IndexMut, RangeBounds};
+use core::slice;
+
+use super::ring_slice::{RingSlice, RingSliceMut};
+use super::sampling::RandomSource;
+use super::slot::Slot;
+use super::traits::{FromElements, Slice, SliceMut};
+use super::{Box, Vec};
+
+/// Ring buffer with a fixed length.
+///
+/// Elements are pushed and popped from the buffer simultaneously
+/// in order to retain a consistent length.
+///
+/// A `Fixed` ring buffer can be created around any type with a slice to write to.
+///
+/// # Layout
+///
+/// `Fixed<S>` is `repr(C)`: a `usize` holding the physical index of the oldest
+/// element (`first`), followed by the storage `S`. For `Fixed<[T; N]>` the ring
+/// is therefore self-contained and can be placed in shared memory as is. Any
+/// ring can be described by `first`, `as_ptr()` and `capacity()`, where the
+/// element at logical index `i` lives at `as_ptr().add((first + i) % capacity)`,
+/// and rebuilt elsewhere with `from_raw_parts(first, slice)`, e.g. over a
+/// `&mut [T]` made with `slice::from_raw_parts_mut`.
+///
+/// # Zero-sized elements
+///
+/// Zero-sized elements such as `()` are supported: the storage still has a
+/// length (e.g. `[(); N]`, or a `Vec<()>` that allocates nothing), and all
+/// index arithmetic works on that length only. Such a ring is a pure slot
+/// tracker, where `first()` says which of the `N` slots the next `push`
+/// claims.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[repr(C)]
+pub struct Fixed<S> {
+    first: usize,
+    data: S,
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice,
+{
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.slice().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of element slots, which for a `Fixed` ring is always its length.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    /// Pointer to the slot at physical index 0, see the layout section of the
+    /// type docs.
+    #[inline]
+    pub fn as_ptr(&self) -> *const S::Element {
+        self.data.slice().as_ptr()
+    }
+
+    /// Mutable pointer to the slot at physical index 0.
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut S::Element
+    where
+        S: SliceMut,
+    {
+        self.data.slice_mut().as_mut_ptr()
+    }
+
+    pub fn push(&mut self, item: S::Element) -> S::Element
+    where
+        S: SliceMut,
+    {
+        self.next_slot().replace(item)
+    }
+
+    /// Like `push`, but drops the evicted element in place instead of
+    /// returning it.
+    #[inline]
+    pub fn push_overwrite(&mut self, item: S::Element)
+    where
+        S: SliceMut,
+    {
+        self.next_slot().set(item);
+    }
+
+    /// Offers `item` to `merge` together with the newest element first; if
+    /// `merge` folds it in and returns `true`, nothing is pushed. Otherwise
+    /// `item` is pushed like `push_overwrite`. Returns whether it merged.
+    ///
+    /// Collapses runs of events such as consecutive mouse moves, so they do
+    /// not flood the ring.
+    #[inline]
+    pub fn push_coalesce<F>(&mut self, item: S::Element, merge: F) -> bool
+    where
+        S: SliceMut,
+        F: FnOnce(&mut S::Element, &S::Element) -> bool,
+    {
+        let merged = merge(self.get_back_mut(0), &item);
+        if !merged {
+            self.push_overwrite(item);
+        }
+        merged
+    }
+
+    /// Claims the slot of the oldest element for a new one, making it the
+    /// newest.
+    #[inline]
+    fn next_slot(&mut self) -> Slot<'_, S::Element>
+    where
+        S: SliceMut,
+    {
+        self.strict_check();
+        let slot = self.first;
+        self.first = wrapping_increment(slot, self.len());
+        Slot::new(self.data.slice_mut(), slot).expect("ring first index out of bounds")
+    }
+
+    /// Panics if the ring's internal invariants do not hold: the storage is
+    /// not empty and `first` is within it.
+    #[inline]
+    pub(crate) fn check_invariants(&self) {
+        assert!(!self.is_empty(), "ring storage is empty");
+        assert!(
+            self.first < self.len(),
+            "ring first index {} out of bounds for length {}",
+            self.first,
+            self.len()
+        );
+    }
+
+    /// Runs `check_invariants` when the `strict-checks` feature is enabled.
+    #[inline(always)]
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        self.check_invariants();
+    }
+
+    #[inline]
+    fn wrap_index(&self, index: usize) -> usize {
+        let len = self.len();
+        if len.is_power_of_two() {
+            // Exact even if the sum overflows, as `len` divides 2^BITS.
+            return self.first.wrapping_add(index) & (len - 1);
+        }
+        // Only indices that are already wrapped skip the division.
+        let index = if index < len { index } else { index % len };
+        add_mod(self.first, index, len)
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> &S::Element {
+        let wrapped_index = self.wrap_index(index);
+        &self.data.slice()[wrapped_index]
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut S::Element
+    where
+        S: SliceMut,
+    {
+        self.strict_check();
+        let wrapped_index = self.wrap_index(index);
+        &mut self.data.slice_mut()[wrapped_index]
+    }
+
+    /// Mutable references to the elements at several logical indices at
+    /// once. Indices wrap like in `get_mut`; returns `None` if two of them
+    /// refer to the same element.
+    #[inline]
+    pub fn get_many_mut<const K: usize>(
+        &mut self,
+        indices: [usize; K],
+    ) -> Option<[&mut S::Element; K]>
+    where
+        S: SliceMut,
+    {
+        self.strict_check();
+        let physical = indices.map(|index| self.wrap_index(index));
+        self.data.slice_mut().get_disjoint_mut(physical).ok()
+    }
+
+    /// Replaces the element at logical `index` and returns the old one.
+    #[inline]
+    pub fn replace(&mut self, index: usize, item: S::Element) -> S::Element
+    where
+        S: SliceMut,
+    {
+        mem::replace(self.get_mut(index), item)
+    }
+
+    /// Applies `f` to the element at logical `index` in place and returns its
+    /// result.
+    #[inline]
+    pub fn update<F, R>(&mut self, index: usize, f: F) -> R
+    where
+        S: SliceMut,
+        F: FnOnce(&mut S::Element) -> R,
+    {
+        f(self.get_mut(index))
+    }
+
+    /// The `n`-th most recent element, 0 being the newest. Like `get`, `n`
+    /// wraps around the length.
+    #[inline]
+    pub fn get_back(&self, n: usize) -> &S::Element {
+        self.get(self.len() - 1 - n % self.len())
+    }
+
+    #[inline]
+    pub fn get_back_mut(&mut self, n: usize) -> &mut S::Element
+    where
+        S: SliceMut,
+    {
+        self.get_mut(self.len() - 1 - n % self.len())
+    }
+
+    /// Physical index of the element at logical index 0, which is also the
+    /// slot the next `push` writes to.
+    #[inline]
+    pub fn first(&self) -> usize {
+        self.first
+    }
+
+    /// Sets the physical index of the oldest element. `index` wraps around the
+    /// length; see `try_set_first` for a checked version.
+    #[inline]
+    pub fn set_first(&mut self, index: usize) {
+        self.first = index % self.len();
+        self.strict_check();
+    }
+
+    /// Like `set_first`, but fails instead of wrapping an out-of-range `index`.
+    #[inline]
+    pub fn try_set_first(&mut self, index: usize) -> Result<(), IndexError> {
+        self.check_index(index)?;
+        self.first = index;
+        Ok(())
+    }
+
+    /// Skips the elements of `iter` that its size hint shows would be
+    /// overwritten by later ones, advancing `first` as if they had been
+    /// pushed. Returns how many were skipped.
+    #[inline]
+    pub(crate) fn skip_overwritten<I>(&mut self, iter: &mut I) -> usize
+    where
+        I: Iterator<Item = S::Element>,
+    {
+        let skipped = skip_to_tail(iter, self.len());
+        self.first = add_mod(self.first, skipped % self.len(), self.len());
+        skipped
+    }
+
+    /// Physical index of the element at logical `index`.
+    #[inline]
+    pub fn logical_to_physical(&self, index: usize) -> Result<usize, IndexError> {
+        self.check_index(index)?;
+        Ok(add_mod(self.first, index, self.len()))
+    }
+
+    /// Logical index of the element at physical `index`.
+    #[inline]
+    pub fn physical_to_logical(&self, index: usize) -> Result<usize, IndexError> {
+        self.check_index(index)?;
+        Ok(sub_mod(index, self.first, self.len()))
+    }
+
+    #[inline]
+    fn check_index(&self, index: usize) -> Result<(), IndexError> {
+        if index < self.len() {
+            Ok(())
+        } else {
+            Err(IndexError {
+                index,
+                len: self.len(),
+            })
+        }
+    }
+
+    /// Rotates the ring so that the element at logical index `n` becomes the first.
+    #[inline]
+    pub fn rotate_left(&mut self, n: usize) {
+        self.first = add_mod(self.first, n % self.len(), self.len());
+        self.strict_check();
+    }
+
+    /// Rotates the ring so that the last `n` elements become the first.
+    #[inline]
+    pub fn rotate_right(&mut self, n: usize) {
+        self.first = sub_mod(self.first, n % self.len(), self.len());
+        self.strict_check();
+    }
+
+    #[inline]
+    pub fn slices(&self) -> (&[S::Element], &[S::Element]) {
+        let (end, start) = self.data.slice().split_at(self.first);
+        (start, end)
+    }
+
+    #[inline]
+    pub fn slices_mut(&mut self) -> (&mut [S::Element], &mut [S::Element])
+    where
+        S: SliceMut,
+    {
+        self.strict_check();
+        let (end, start) = self.data.slice_mut().split_at_mut(self.first);
+        (start, end)
+    }
+
+    /// Splits the ring into mutable views of the logical ranges `..mid` and
+    /// `mid..`. Panics if `mid > len()`.
+    #[inline]
+    pub fn split_at_logical_mut(
+        &mut self,
+        mid: usize,
+    ) -> (RingSliceMut<'_, S::Element>, RingSliceMut<'_, S::Element>)
+    where
+        S: SliceMut,
+    {
+        let (start, end) = self.slices_mut();
+        RingSliceMut::new(start, end).split_at_mut(mid)
+    }
+
+    /// Rotates the storage in place so the elements are physically in logical
+    /// order, and returns them as one slice.
+    #[inline]
+    pub fn make_contiguous(&mut self) -> &mut [S::Element]
+    where
+        S: SliceMut,
+    {
+        let first = mem::take(&mut self.first);
+        let data = self.data.slice_mut();
+        data.rotate_left(first);
+        data
+    }
+
+    /// Sorts the elements in logical order, stably. Leaves `first` at 0.
+    #[inline]
+    pub fn sort(&mut self)
+    where
+        S: SliceMut,
+        S::Element: Ord,
+    {
+        self.make_contiguous().sort();
+    }
+
+    #[inline]
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        S: SliceMut,
+        F: FnMut(&S::Element, &S::Element) -> Ordering,
+    {
+        self.make_contiguous().sort_by(compare);
+    }
+
+    #[inline]
+    pub fn sort_by_key<K, F>(&mut self, f: F)
+    where
+        S: SliceMut,
+        F: FnMut(&S::Element) -> K,
+        K: Ord,
+    {
+        self.make_contiguous().sort_by_key(f);
+    }
+
+    /// Sorts the elements in logical order without allocating; equal
+    /// elements may be reordered. Leaves `first` at 0.
+    #[inline]
+    pub fn sort_unstable(&mut self)
+    where
+        S: SliceMut,
+        S::Element: Ord,
+    {
+        self.make_contiguous().sort_unstable();
+    }
+
+    #[inline]
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        S: SliceMut,
+        F: FnMut(&S::Element, &S::Element) -> Ordering,
+    {
+        self.make_contiguous().sort_unstable_by(compare);
+    }
+
+    /// References to the `K` elements from logical index `start` on, or
+    /// `None` if the ring ends before. A run that does not cross the physical
+    /// end is taken from the storage in one piece.
+    #[inline]
+    pub fn get_batch<const K: usize>(&self, start: usize) -> Option<[&S::Element; K]> {
+        let end = start.checked_add(K).filter(|&end| end <= self.len())?;
+        let run = self.range(start..end);
+        match run.as_slices() {
+            (head, []) => <&[S::Element; K]>::try_from(head)
+                .ok()
+                .map(|batch| batch.each_ref()),
+            _ => Some(array::from_fn(|i| run.get(i).unwrap())),
+        }
+    }
+
+    /// View of the elements in a logical `range`. Panics if the range is out
+    /// of bounds, like slicing.
+    pub fn range<R>(&self, range: R) -> RingSlice<'_, S::Element>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "ring range out of bounds"
+        );
+
+        let (head, tail) = self.slices();
+        if start >= head.len() {
+            RingSlice::new(&tail[start - head.len()..end - head.len()], &[])
+        } else if end <= head.len() {
+            RingSlice::new(&head[start..end], &[])
+        } else {
+            RingSlice::new(&head[start..], &tail[..end - head.len()])
+        }
+    }
+
+    #[inline]
+    pub fn iter_loop(&self) -> Skip<Cycle<slice::Iter<'_, S::Element>>> {
+        self.data.slice().iter().cycle().skip(self.first)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Take<Skip<Cycle<slice::Iter<'_, S::Element>>>> {
+        self.iter_loop().take(self.data.slice().len())
+    }
+
+    #[inline]
+    pub fn iter_mut(
+        &mut self,
+    ) -> Chain<slice::IterMut<'_, S::Element>, slice::IterMut<'_, S::Element>>
+    where
+        S: SliceMut,
+    {
+        let (start, end) = self.slices_mut();
+        start.iter_mut().chain(end.iter_mut())
+    }
+
+    #[inline]
+    pub fn from_raw_parts(first: usize, data: S) -> Self {
+        assert!(first < data.slice().len());
+        Fixed { first, data }
+    }
+
+    /// # Safety
+    ///
+    /// `first` must be less than the length of `data`, otherwise `push`
+    /// writes out of bounds.
+    #[inline]
+    pub unsafe fn from_raw_parts_unchecked(first: usize, data: S) -> Self {
+        let ring = Fixed { first, data };
+        ring.strict_check();
+        ring
+    }
+
+    #[inline]
+    pub fn into_raw_parts(self) -> (usize, S) {
+        let Fixed { first, data } = self;
+        (first, data)
+    }
+
+    /// Backing storage, in physical order.
+    #[inline]
+    pub fn storage(&self) -> &S {
+        &self.data
+    }
+
+    /// Backing storage as a mutable slice, in physical order.
+    ///
+    /// Only the slice is handed out, as resizing the storage (e.g. a `Vec`)
+    /// could leave `first` out of bounds.
+    #[inline]
+    pub fn storage_mut(&mut self) -> &mut [S::Element]
+    where
+        S: SliceMut,
+    {
+        self.data.slice_mut()
+    }
+
+    /// Converts the storage with `f`, keeping the elements where they are.
+    ///
+    /// Panics if the new storage has a different length, as the logical order
+    /// would no longer hold.
+    #[inline]
+    pub fn map_storage<S2, F>(self, f: F) -> Fixed<S2>
+    where
+        S2: Slice,
+        F: FnOnce(S) -> S2,
+    {
+        let len = self.len();
+        let data = f(self.data);
+        assert_eq!(data.slice().len(), len, "storage length changed");
+        Fixed {
+            first: self.first,
+            data,
+        }
+    }
+
+    /// Borrows the ring as a ring over a mutable slice, so code written for
+    /// `Fixed<&mut [T]>` works with any storage. Pushes and rotations through
+    /// the view carry over to `self` once it is dropped.
+    #[inline]
+    pub fn as_view(&mut self) -> View<'_, S::Element>
+    where
+        S: SliceMut,
+    {
+        View {
+            ring: Fixed {
+                first: self.first,
+                data: self.data.slice_mut(),
+            },
+            first: &mut self.first,
+        }
+    }
+
+    /// Borrows the ring as a ring over a shared slice.
+    #[inline]
+    pub fn as_ref_view(&self) -> Fixed<&[S::Element]> {
+        Fixed {
+            first: self.first,
+            data: self.data.slice(),
+        }
+    }
+
+    /// Takes back the storage, in physical order.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.data
+    }
+
+    /// Takes back the storage after rotating it so the oldest element comes
+    /// first.
+    #[inline]
+    pub fn into_inner_ordered(mut self) -> S
+    where
+        S: SliceMut,
+    {
+        self.make_contiguous();
+        self.data
+    }
+
+    /// Whether both rings hold the same cyclic sequence, i.e. are equal after
+    /// rotating one of them. Runs in linear time.
+    pub fn eq_ignore_rotation<S2>(&self, other: &Fixed<S2>) -> bool
+    where
+        S2: Slice,
+        S::Element: PartialEq<S2::Element>,
+        S2::Element: PartialEq,
+    {
+        let n = self.len();
+        if n != other.len() {
+            return false;
+        }
+
+        // Knuth-Morris-Pratt search for `other` in `self` repeated twice.
+        let mut failure = alloc::vec![0; n];
+        let mut k = 0;
+        for i in 1..n {
+            while k > 0 && other.get(i) != other.get(k) {
+                k = failure[k - 1];
+            }
+            if other.get(i) == other.get(k) {
+                k += 1;
+            }
+            failure[i] = k;
+        }
+        let mut matched = 0;
+        for i in 0..2 * n - 1 {
+            while matched > 0 && *self.get(i) != *other.get(matched) {
+                matched = failure[matched - 1];
+            }
+            if *self.get(i) == *other.get(matched) {
+                matched += 1;
+                if matched == n {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Rotates the ring to its lexicographically smallest rotation, the same
+    /// for all rings that are `eq_ignore_rotation`, and returns the logical
+    /// index that became the first.
+    pub fn canonicalize(&mut self) -> usize
+    where
+        S::Element: Ord,
+    {
+        // Two candidates `i` and `j` race; comparing `k` elements past each
+        // rules out the losing candidate and all starts within the compared
+        // prefix.
+        let n = self.len();
+        let (mut i, mut j, mut k) = (0, 1, 0);
+        while i < n && j < n && k < n {
+            match self.get(i + k).cmp(self.get(j + k)) {
+                Ordering::Equal => {
+                    k += 1;
+                    continue;
+                }
+                Ordering::Greater => i += k + 1,
+                Ordering::Less => j += k + 1,
+            }
+            if i == j {
+                j += 1;
+            }
+            k = 0;
+        }
+        let start = i.min(j);
+        self.rotate_left(start);
+        start
+    }
+
+    /// Draws `k` distinct logical indices uniformly at random, or all of them if
+    /// `k` is not less than the length.
+    ///
+    /// Uses Floyd's algorithm, which needs `O(k)` memory and `O(k²)` time and
+    /// never touches the elements, so it suits batches that are small compared
+    /// to the window.
+    pub fn sample_indices<R>(&self, rng: &mut R, k: usize) -> Vec<usize>
+    where
+        R: RandomSource + ?Sized,
+    {
+        let n = self.len();
+        let k = k.min(n);
+        let mut chosen = Vec::with_capacity(k);
+        for j in n - k..n {
+            let t = rng.below(j + 1);
+            chosen.push(if chosen.contains(&t) { j } else { t });
+        }
+        chosen
+    }
+
+    /// Draws `k` distinct elements uniformly at random, see `sample_indices`.
+    #[inline]
+    pub fn sample<R>(&self, rng: &mut R, k: usize) -> Vec<&S::Element>
+    where
+        R: RandomSource + ?Sized,
+    {
+        self.sample_indices(rng, k)
+            .into_iter()
+            .map(|index| self.get(index))
+            .collect()
+    }
+
+    /// Groups the elements into consecutive buckets of `bucket_len` elements in
+    /// logical order and reduces each bucket with `f`.
+    ///
+    /// The last bucket is shorter if the length is not a multiple of
+    /// `bucket_len`. Panics if `bucket_len` is zero.
+    #[inline]
+    pub fn aggregate_buckets<F, R>(
+        &self,
+        bucket_len: usize,
+        f: F,
+    ) -> AggregateBuckets<'_, S::Element, F>
+    where
+        F: FnMut(Bucket<'_, S::Element>) -> R,
+    {
+        assert!(bucket_len > 0);
+        let (start, end) = self.slices();
+        AggregateBuckets {
+            items: start.iter().chain(end.iter()),
+            remaining: self.len(),
+            bucket_len,
+            f,
+        }
+    }
+}
+
+// The wrapping helpers below select between the candidates with a mask
+// instead of a branch, and never divide, so `push` does neither. `wrap_index`,
+// behind `get`, still branches on the length and divides indices of a whole
+// lap or more.
+
+/// `(index + 1) % len` for `index < len`.
+#[inline]
+fn wrapping_increment(index: usize, len: usize) -> usize {
+    let next = index + 1;
+    next & ((next != len) as usize).wrapping_neg()
+}
+
+/// `(a + b) % len` for `a, b < len`, without overflowing near `usize::MAX`
+/// (reachable with zero-sized elements).
+#[inline]
+fn add_mod(a: usize, b: usize, len: usize) -> usize {
+    let gap = len - a;
+    let wraps = ((b >= gap) as usize).wrapping_neg();
+    (b.wrapping_sub(gap) & wraps) | (a.wrapping_add(b) & !wraps)
+}
+
+/// `(a - b) mod len` for `a, b < len`.
+#[inline]
+fn sub_mod(a: usize, b: usize, len: usize) -> usize {
+    let wraps = ((b > a) as usize).wrapping_neg();
+    a.wrapping_sub(b).wrapping_add(len & wraps)
+}
+
+/// Bulk copies, each done with at most two `copy_from_slice` calls across the
+/// wrap.
+impl<S> Fixed<S>
+where
+    S: Slice,
+    S::Element: Copy,
+{
+    /// Overwrites the elements at logical `offset..offset + src.len()`.
+    /// Panics if that range is out of bounds.
+    pub(crate) fn copy_in(&mut self, offset: usize, src: &[S::Element])
+    where
+        S: SliceMut,
+    {
+        let len = self.len();
+        assert!(
+            offset <= len && src.len() <= len - offset,
+            "ring range out of bounds"
+        );
+        if src.is_empty() {
+            return;
+        }
+        let start = add_mod(self.first, offset, len);
+        let (src_head, src_tail) = src.split_at(src.len().min(len - start));
+        let data = self.data.slice_mut();
+        data[start..start + src_head.len()].copy_from_slice(src_head);
+        data[..src_tail.len()].copy_from_slice(src_tail);
+    }
+
+    /// Copies the elements at logical `offset..offset + dst.len()` into `dst`.
+    /// Panics if that range is out of bounds.
+    pub(crate) fn copy_out(&self, offset: usize, dst: &mut [S::Element]) {
+        self.range(offset..offset + dst.len()).copy_to_slice(dst);
+    }
+
+    /// Pushes every element of `src`, oldest first. Only the last `len()`
+    /// elements of a longer `src` remain.
+    #[inline]
+    pub fn extend_from_slice(&mut self, src: &[S::Element])
+    where
+        S: SliceMut,
+    {
+        let src = &src[src.len().saturating_sub(self.len())..];
+        // The pushed elements take the slots of the oldest ones, which then
+        // become the newest.
+        self.copy_in(0, src);
+        self.rotate_left(src.len());
+    }
+
+    /// Copies the elements, oldest first, into `dst`. Panics if the lengths
+    /// differ.
+    #[inline]
+    pub fn copy_to_slice(&self, dst: &mut [S::Element]) {
+        assert_eq!(dst.len(), self.len(), "destination length differs");
+        self.copy_out(0, dst);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+type ArchivedElement<S> = <<S as rkyv::Archive>::Archived as Slice>::Element;
+
+/// Zero-copy access to an archived ring, in the same logical order as `Fixed`.
+///
+/// An archive may come from untrusted bytes, so an out-of-range `first` is
+/// clamped instead of trusted.
+#[cfg(feature = "rkyv")]
+impl<S> ArchivedFixed<S>
+where
+    S: rkyv::Archive,
+    S::Archived: Slice,
+{
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.slice().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn first(&self) -> usize {
+        (self.first.to_native() as usize).min(self.len())
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&ArchivedElement<S>> {
+        let (start, end) = self.slices();
+        start.get(index).or_else(|| end.get(index - start.len()))
+    }
+
+    #[inline]
+    pub fn slices(&self) -> (&[ArchivedElement<S>], &[ArchivedElement<S>]) {
+        let (end, start) = self.data.slice().split_at(self.first());
+        (start, end)
+    }
+
+    #[inline]
+    pub fn iter(
+        &self,
+    ) -> Chain<slice::Iter<'_, ArchivedElement<S>>, slice::Iter<'_, ArchivedElement<S>>> {
+        let (start, end) = self.slices();
+        start.iter().chain(end.iter())
+    }
+}
+
+/// Ring over a mutable slice, borrowed from another ring by `Fixed::as_view`.
+///
+/// Derefs to `Fixed<&mut [T]>` and writes its `first` back to the ring it
+/// was borrowed from when dropped.
+#[derive(Debug)]
+pub struct View<'a, T> {
+    ring: Fixed<&'a mut [T]>,
+    first: &'a mut usize,
+}
+
+impl<'a, T> Deref for View<'a, T> {
+    type Target = Fixed<&'a mut [T]>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.ring
+    }
+}
+
+impl<T> DerefMut for View<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.ring
+    }
+}
+
+impl<T> Drop for View<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        *self.first = self.ring.first;
+    }
+}
+
+/// Index outside of a ring.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IndexError {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index {} out of bounds for ring of length {}",
+            self.index, self.len
+        )
+    }
+}
+
+/// An iterator ran out before filling an array-backed ring.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TooFewElements {
+    /// Number of elements the iterator yielded.
+    pub count: usize,
+    pub expected: usize,
+}
+
+impl fmt::Display for TooFewElements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "iterator yielded {} elements, expected {}",
+            self.count, self.expected
+        )
+    }
+}
+
+/// Elements of one bucket passed to the reducer of `Fixed::aggregate_buckets`.
+pub type Bucket<'a, T> = Take<Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>>;
+
+/// Iterator over the reduced buckets of a `Fixed` ring.
+#[derive(Clone, Debug)]
+pub struct AggregateBuckets<'a, T, F> {
+    items: Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>,
+    remaining: usize,
+    bucket_len: usize,
+    f: F,
+}
+
+impl<'a, T, F, R> Iterator for AggregateBuckets<'a, T, F>
+where
+    F: FnMut(Bucket<'a, T>) -> R,
+{
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let n = self.bucket_len.min(self.remaining);
+        let bucket = self.items.clone().take(n);
+        self.items.nth(n - 1);
+        self.remaining -= n;
+        Some((self.f)(bucket))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.remaining.div_ceil(self.bucket_len);
+        (n, Some(n))
+    }
+}
+
+impl<'a, T, F, R> ExactSizeIterator for AggregateBuckets<'a, T, F> where F: FnMut(Bucket<'a, T>) -> R
+{}
+
+#[cfg(feature = "postcard")]
+impl<S> Fixed<S>
+where
+    S: Slice,
+{
+    /// Encodes the ring with postcard into `buf`, returning the number of
+    /// bytes used. Works without std, e.g. to persist a ring to flash.
+    #[inline]
+    pub fn save(&self, buf: &mut [u8]) -> Result<usize, postcard::Error>
+    where
+        S::Element: serde::Serialize,
+    {
+        postcard::to_slice(self, buf).map(|used| used.len())
+    }
+
+    /// Decodes a ring written by `save`.
+    #[inline]
+    pub fn load<'de>(bytes: &'de [u8]) -> Result<Self, postcard::Error>
+    where
+        S: FromElements,
+        S::Element: serde::Deserialize<'de>,
+    {
+        postcard::from_bytes(bytes)
+    }
+}
+
+impl<T> Fixed<&[T]> {
+    /// Clones the elements into an owned ring with the same rotation.
+    #[inline]
+    pub fn to_vec(&self) -> Fixed<Vec<T>>
+    where
+        T: Clone,
+    {
+        Fixed::from_raw_parts(self.first, self.data.to_vec())
+    }
+
+    /// Like `to_vec`, without the spare capacity of a `Vec`.
+    #[inline]
+    pub fn to_boxed(&self) -> Fixed<Box<[T]>>
+    where
+        T: Clone,
+    {
+        Fixed::from_raw_parts(self.first, Box::from(self.data))
+    }
+}
+
+impl<T> Fixed<&mut [T]> {
+    /// Clones the elements into an owned ring with the same rotation.
+    #[inline]
+    pub fn to_vec(&self) -> Fixed<Vec<T>>
+    where
+        T: Clone,
+    {
+        Fixed::from_raw_parts(self.first, self.data.to_vec())
+    }
+
+    /// Like `to_vec`, without the spare capacity of a `Vec`.
+    #[inline]
+    pub fn to_boxed(&self) -> Fixed<Box<[T]>>
+    where
+        T: Clone,
+    {
+        Fixed::from_raw_parts(self.first, Box::from(&*self.data))
+    }
+}
+
+impl<T> Fixed<Vec<T>> {
+    /// Like `Fixed::<[T; N]>::latest_from_iter`, keeping up to `len`
+    /// elements; the ring is shorter if `iter` has fewer. Panics if there
+    /// are none.
+    pub fn latest_from_iter<I>(iter: I, len: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        skip_to_tail(&mut iter, len);
+        let mut latest = Fixed::from(iter.by_ref().take(len).collect::<Vec<_>>());
+        latest.extend(iter);
+        latest
+    }
+
+    /// Moves the newest `len - at` elements into a new ring and keeps the
+    /// oldest `at`, both in logical order and unrotated. Panics unless
+    /// `0 < at < len`, since neither ring may be empty.
+    pub fn split_off(&mut self, at: usize) -> Fixed<Vec<T>> {
+        assert!(0 < at && at < self.len(), "split point out of bounds");
+        self.make_contiguous();
+        Fixed::from(self.data.split_off(at))
+    }
+}
+
+impl<T, const N: usize> Fixed<[T; N]> {
+    /// Moves the elements to the heap, keeping the rotation.
+    #[inline]
+    pub fn into_boxed(self) -> Fixed<Box<[T]>> {
+        Fixed::from_raw_parts(self.first, Box::new(self.data))
+    }
+
+    /// Fills the ring with the first `N` elements of `iter`, without going
+    /// through a `Vec`. Fails if there are fewer; the elements taken so far
+    /// are dropped.
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, TooFewElements>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let elements = [(); N].map(|_| iter.next());
+        let count = elements.iter().take_while(|item| item.is_some()).count();
+        if count < N {
+            return Err(TooFewElements { count, expected: N });
+        }
+        Ok(Fixed::from(elements.map(Option::unwrap)))
+    }
+
+    /// Fills the ring with the last `N` elements of `iter`, oldest first.
+    /// Fails if there are fewer.
+    ///
+    /// Elements the size hint of `iter` shows to be followed by at least `N`
+    /// more are skipped with `nth` instead of being pushed, which is a bulk
+    /// operation for slices, ranges and the like.
+    pub fn latest_from_iter<I>(iter: I) -> Result<Self, TooFewElements>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        skip_to_tail(&mut iter, N);
+        let mut latest = Fixed::from([(); N].map(|_| None));
+        let mut count = 0;
+        for item in iter {
+            latest.push_overwrite(Some(item));
+            count += 1;
+        }
+        if count < N {
+            return Err(TooFewElements { count, expected: N });
+        }
+        let first = latest.first();
+        Ok(Fixed::from_raw_parts(
+            first,
+            latest.into_inner().map(Option::unwrap),
+        ))
+    }
+}
+
+/// Skips the elements of `iter` that its size hint shows to be followed by
+/// at least `keep` more. Returns how many were skipped.
+#[inline]
+fn skip_to_tail<I>(iter: &mut I, keep: usize) -> usize
+where
+    I: Iterator,
+{
+    let excess = iter.size_hint().0.saturating_sub(keep);
+    if excess > 0 {
+        iter.nth(excess - 1);
+    }
+    excess
+}
+
+impl<T> Fixed<Box<[T]>> {
+    /// Moves the elements into an array-backed ring with the same rotation.
+    /// Gives the ring back if its length is not `N`.
+    pub fn try_into_array<const N: usize>(self) -> Result<Fixed<[T; N]>, Self> {
+        let first = self.first;
+        match Box::<[T; N]>::try_from(self.data) {
+            Ok(data) => Ok(Fixed::from_raw_parts(first, *data)),
+            Err(data) => Err(Fixed { first, data }),
+        }
+    }
+
+    /// Like `Fixed::<Vec<T>>::split_off`. Both halves get their own
+    /// allocation.
+    pub fn split_off(&mut self, at: usize) -> Fixed<Box<[T]>> {
+        assert!(0 < at && at < self.len(), "split point out of bounds");
+        self.make_contiguous();
+        let mut data = mem::take(&mut self.data).into_vec();
+        let newest = data.split_off(at);
+        self.data = data.into_boxed_slice();
+        Fixed::from(newest.into_boxed_slice())
+    }
+}
+
+impl<S> From<S> for Fixed<S>
+where
+    S: Slice,
+{
+    #[inline]
+    fn from(data: S) -> Self {
+        Self::from_raw_parts(0, data)
+    }
+}
+
+/// Equality across storage types, with the same meaning as the derived
+/// equality: the same `first` and the same elements in physical order.
+macro_rules! impl_eq_across_storage {
+    ($([$($generics:tt)*] $lhs:ty, $rhs:ty;)*) => {$(
+        impl<$($generics)* T, U> PartialEq<Fixed<$rhs>> for Fixed<$lhs>
+        where
+            T: PartialEq<U>,
+        {
+            #[inline]
+            fn eq(&self, other: &Fixed<$rhs>) -> bool {
+                self.first == other.first && self.data.slice() == other.data.slice()
+            }
+        }
+    )*};
+}
+
+impl_eq_across_storage! {
+    [const N: usize,] [T; N], Vec<U>;
+    [const N: usize,] [T; N], Box<[U]>;
+    ['a, const N: usize,] [T; N], &'a [U];
+    ['a, const N: usize,] [T; N], &'a mut [U];
+    [const N: usize,] Vec<T>, [U; N];
+    [] Vec<T>, Box<[U]>;
+    ['a,] Vec<T>, &'a [U];
+    ['a,] Vec<T>, &'a mut [U];
+    [const N: usize,] Box<[T]>, [U; N];
+    [] Box<[T]>, Vec<U>;
+    ['a,] Box<[T]>, &'a [U];
+    ['a,] Box<[T]>, &'a mut [U];
+    ['a, const N: usize,] &'a [T], [U; N];
+    ['a,] &'a [T], Vec<U>;
+    ['a,] &'a [T], Box<[U]>;
+    ['a, 'b,] &'a [T], &'b mut [U];
+    ['a, const N: usize,] &'a mut [T], [U; N];
+    ['a,] &'a mut [T], Vec<U>;
+    ['a,] &'a mut [T], Box<[U]>;
+    ['a, 'b,] &'a mut [T], &'b [U];
+}
+
+impl<S, T> FromIterator<T> for Fixed<S>
+where
+    S: Slice<Element = T> + FromIterator<T>,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let data = S::from_iter(iter);
+        Self::from(data)
+    }
+}
+
+impl<S> Index<usize> for Fixed<S>
+where
+    S: Slice,
+{
+    type Output = S::Element;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index)
+    }
+}
+
+impl<S> IndexMut<usize> for Fixed<S>
+where
+    S: SliceMut,
+{
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index)
+    }
+}
+
+impl<S> Extend<S::Element> for Fixed<S>
+where
+    S: SliceMut,
+{
+    /// Elements known from the size hint to be overwritten by later ones are
+    /// skipped instead of pushed.
+    fn extend<T: IntoIterator<Item = S::Element>>(&mut self, iter: T) {
+        let mut iter = iter.into_iter();
+        self.skip_overwritten(&mut iter);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_boxed_slice() {
+        let mut rb = Fixed::from(vec![0; 3].into_boxed_slice());
+        assert_eq!(rb.push(1), 0);
+        assert_eq!(rb.push(2), 0);
+        assert_eq!(rb.push(3), 0);
+        assert_eq!(rb.push(4), 1);
+    }
+
+    #[test]
+    fn test_array() {
+        let mut rb = Fixed::from([0i32; 3]);
+        assert_eq!(rb.push(1), 0);
+        assert_eq!(rb.push(2), 0);
+        assert_eq!(rb.push(3), 0);
+        assert_eq!(rb.push(4), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_empty_vec() {
+        let _ = Fixed::from(Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut rb = Fixed::from(vec![1, 2, 3]);
+        assert_eq!(rb.push(4), 1);
+        assert_eq!(rb.push(5), 2);
+        assert_eq!(rb.push(6), 3);
+        assert_eq!(rb.push(7), 4);
+    }
+
+    #[test]
+    fn test_get_wraps() {
+        let mut rb = Fixed::from([0i32; 4]);
+        rb.extend([1, 2, 3, 4, 5]);
+        assert_eq!(rb[0], 2);
+        assert_eq!(rb[3], 5);
+        assert_eq!(rb[4], 2);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut rb = Fixed::from(vec![1, 2, 3, 4]);
+        rb.rotate_left(1);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), [2, 3, 4, 1]);
+        rb.rotate_right(3);
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), [3, 4, 1, 2]);
+        rb.rotate_right(4);
+        assert_eq!(rb[0], 3);
+    }
+
+    #[test]
+    fn test_aggregate_buckets() {
+        let mut rb = Fixed::from([0i32; 5]);
+        rb.extend([1, 2, 3, 4, 5, 6, 7]);
+        let max: Vec<i32> = rb
+            .aggregate_buckets(2, |b| b.copied().max().unwrap())
+            .collect();
+        assert_eq!(max, [4, 6, 7]);
+        let mean: Vec<i32> = rb.aggregate_buckets(5, |b| b.sum::<i32>() / 5).collect();
+        assert_eq!(mean, [5]);
+        assert_eq!(rb.aggregate_buckets(3, |b| b.last().copied()).len(), 2);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut rng = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let rb: Fixed<Vec<u32>> = (0..100).collect();
+        for _ in 0..20 {
+            let mut picked = rb.sample(&mut rng, 10);
+            assert_eq!(picked.len(), 10);
+            picked.sort();
+            picked.dedup();
+            assert_eq!(picked.len(), 10);
+        }
+        let mut all = rb.sample_indices(&mut rng, 1000);
+        all.sort();
+        assert_eq!(all, (0..100).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv_archive() {
+        let mut rb = Fixed::from(vec![0u32; 4]);
+        rb.extend([1, 2, 3, 4, 5]);
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&rb).unwrap();
+        let archived =
+            rkyv::access::<ArchivedFixed<Vec<u32>>, rkyv::rancor::Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 4);
+        assert_eq!(archived.get(0).map(|x| x.to_native()), Some(2));
+        let items: Vec<u32> = archived.iter().map(|x| x.to_native()).collect();
+        assert_eq!(items, [2, 3, 4, 5]);
+
+        let back: Fixed<Vec<u32>> = rkyv::deserialize::<_, rkyv::rancor::Error>(archived).unwrap();
+        assert_eq!(back, rb);
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_postcard_save_load() {
+        let mut rb = Fixed::from([0u16; 4]);
+        rb.extend([1, 2, 300, 4, 5]);
+        let mut buf = [0u8; 32];
+        let used = rb.save(&mut buf).unwrap();
+        let back = Fixed::<[u16; 4]>::load(&buf[..used]).unwrap();
+        assert!(back.iter().eq(rb.iter()));
+        assert!(rb.save(&mut buf[..2]).is_err());
+        assert!(Fixed::<[u16; 3]>::load(&buf[..used]).is_err());
+    }
+
+    #[test]
+    fn test_raw_layout() {
+        assert_eq!(mem::offset_of!(Fixed<[u32; 4]>, first), 0);
+        assert_eq!(
+            mem::size_of::<Fixed<[u32; 4]>>(),
+            mem::size_of::<usize>() + 16
+        );
+
+        let mut rb = Fixed::from([0u32; 4]);
+        for i in 1..=6 {
+            rb.push(i);
+        }
+        let (first, capacity, ptr) = (rb.first(), rb.capacity(), rb.as_mut_ptr());
+        let view =
+            Fixed::from_raw_parts(first, unsafe { slice::from_raw_parts_mut(ptr, capacity) });
+        assert!(view.iter().eq(&[3, 4, 5, 6]));
+        assert_eq!(unsafe { *ptr.add((first + 1) % capacity) }, 4);
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let mut rb = Fixed::from(vec![0.0f32; 3].into_boxed_slice());
+        for i in 1..=4 {
+            rb.push(i as f32);
+        }
+        assert_eq!(rb.storage()[..], [4.0, 2.0, 3.0]);
+        rb.storage_mut()[0] = 5.0;
+        assert_eq!(rb.clone().into_inner()[..], [5.0, 2.0, 3.0]);
+        assert_eq!(rb.into_inner_ordered()[..], [2.0, 3.0, 5.0]);
+    }
+
+    #[test]
+    fn test_map_storage() {
+        let mut rb = Fixed::from([0u8; 3]);
+        rb.push(1);
+        let boxed: Fixed<Box<[u8]>> = rb.map_storage(Box::from);
+        assert_eq!(boxed.first(), 1);
+        assert!(boxed.iter().eq(&[0, 0, 1]));
+        let vec = boxed.map_storage(Vec::from);
+        assert!(vec.iter().eq(&[0, 0, 1]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_map_storage_length() {
+        Fixed::from(vec![0u8; 3]).map_storage(|mut data| {
+            data.pop();
+            data
+        });
+    }
+
+    #[test]
+    fn test_view() {
+        fn push_twice(rb: &mut Fixed<&mut [u8]>) {
+            rb.push(8);
+            rb.push(9);
+        }
+
+        let mut rb = Fixed::from(vec![0u8; 3]);
+        rb.push(7);
+        push_twice(&mut rb.as_view());
+        assert!(rb.iter().eq(&[7, 8, 9]));
+        rb.as_view().rotate_left(1);
+        assert!(rb.iter().eq(&[8, 9, 7]));
+    }
+
+    #[test]
+    fn test_get_back() {
+        let mut rb = Fixed::from([0; 4]);
+        for i in 1..=6 {
+            rb.push(i);
+        }
+        assert_eq!(*rb.get_back(0), 6);
+        assert_eq!(*rb.get_back(3), 3);
+        assert_eq!(*rb.get_back(4), 6);
+        *rb.get_back_mut(1) = 50;
+        assert!(rb.iter().eq(&[3, 4, 50, 6]));
+    }
+
+    #[test]
+    fn test_replace_update() {
+        let mut rb = Fixed::from(vec![(0, false); 3]);
+        rb.push((1, false));
+        assert_eq!(rb.replace(2, (9, false)), (1, false));
+        let acked = rb.update(2, |event| mem::replace(&mut event.1, true));
+        assert!(!acked);
+        assert_eq!(*rb.get_back(0), (9, true));
+    }
+
+    #[test]
+    fn test_push_overwrite() {
+        use alloc::rc::Rc;
+
+        let tracked = Rc::new(());
+        let mut rb = Fixed::from(vec![Rc::clone(&tracked); 2]);
+        assert_eq!(Rc::strong_count(&tracked), 3);
+        rb.push_overwrite(Rc::new(()));
+        assert_eq!(Rc::strong_count(&tracked), 2);
+        rb.push_overwrite(Rc::new(()));
+        rb.push_overwrite(Rc::clone(&tracked));
+        assert_eq!(Rc::strong_count(&tracked), 2);
+        assert!(Rc::ptr_eq(rb.get_back(0), &tracked));
+    }
+
+    #[test]
+    fn test_push_coalesce() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        enum Event {
+            Move(i32, i32),
+            Click,
+        }
+
+        let merge_moves = |newest: &mut Event, event: &Event| match (newest, event) {
+            (Event::Move(x, y), Event::Move(dx, dy)) => {
+                *x += dx;
+                *y += dy;
+                true
+            }
+            _ => false,
+        };
+
+        let mut rb = Fixed::from([Event::Click; 3]);
+        assert!(!rb.push_coalesce(Event::Move(1, 1), merge_moves));
+        assert!(rb.push_coalesce(Event::Move(2, -1), merge_moves));
+        assert!(!rb.push_coalesce(Event::Click, merge_moves));
+        assert!(!rb.push_coalesce(Event::Move(0, 3), merge_moves));
+        assert!(rb
+            .iter()
+            .eq(&[Event::Move(3, 0), Event::Click, Event::Move(0, 3)]));
+    }
+
+    #[test]
+    fn test_make_contiguous_and_sort() {
+        let mut rb = Fixed::from([0; 5]);
+        rb.extend([9, 4, 7, 1, 8, 2, 6]);
+        assert_eq!(rb.make_contiguous(), [7, 1, 8, 2, 6]);
+        assert_eq!(rb.first(), 0);
+
+        rb.push(3);
+        rb.sort();
+        assert_eq!(rb.first(), 0);
+        assert_eq!(rb.storage(), &[1, 2, 3, 6, 8]);
+        rb.push(0);
+        rb.sort_unstable_by(|a, b| b.cmp(a));
+        assert!(rb.iter().eq(&[8, 6, 3, 2, 0]));
+
+        let mut pairs = Fixed::from(vec![(0, 'x'); 3]);
+        pairs.extend([(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')]);
+        pairs.sort_by_key(|&(key, _)| key);
+        assert!(pairs.iter().eq(&[(1, 'b'), (1, 'd'), (2, 'c')]));
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut rb = Fixed::from(vec![0; 5]);
+        rb.extend(1..=7);
+        let newest = rb.split_off(2);
+        assert_eq!(rb.first(), 0);
+        assert!(rb.iter().eq(&[3, 4]));
+        assert!(newest.iter().eq(&[5, 6, 7]));
+
+        let mut rb = Fixed::from(vec![0; 3].into_boxed_slice());
+        rb.extend(1..=4);
+        let newest = rb.split_off(1);
+        assert_eq!(rb.into_inner(), Box::from([2]));
+        assert!(newest.iter().eq(&[3, 4]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_off_everything() {
+        Fixed::from(vec![1, 2]).split_off(2);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut rb = Fixed::from([(); 3]);
+        for _ in 0..4 {
+            rb.push(());
+        }
+        assert_eq!(rb.first(), 1);
+        assert_eq!(rb.iter().count(), 3);
+        assert_eq!(rb.range(1..).len(), 2);
+        rb.rotate_right(1);
+        assert_eq!(rb.first(), 0);
+
+        let mut rb = Fixed::from(vec![(); usize::MAX]);
+        rb.push_overwrite(());
+        rb.rotate_left(5);
+        assert_eq!(rb.first(), 6);
+        rb.rotate_right(7);
+        assert_eq!(rb.first(), usize::MAX - 1);
+        rb.rotate_left(usize::MAX - 2);
+        assert_eq!(rb.first(), usize::MAX - 3);
+        let _ = rb.get(usize::MAX - 1);
+        assert_eq!(rb.capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let rb = Fixed::from([1, 2, 3]);
+        rb.check_invariants();
+        let broken = Fixed {
+            first: 0,
+            data: [0u8; 0],
+        };
+        assert!(std::panic::catch_unwind(|| broken.check_invariants()).is_err());
+    }
+
+    #[cfg(feature = "strict-checks")]
+    #[test]
+    #[should_panic]
+    fn test_strict_checks() {
+        let _ = unsafe { Fixed::from_raw_parts_unchecked(3, [0u8; 3]) };
+    }
+
+    #[test]
+    fn test_position_validation() {
+        let mut rb = Fixed::from([10, 11, 12, 13]);
+        rb.try_set_first(3).unwrap();
+        assert_eq!(rb.logical_to_physical(1), Ok(0));
+        assert_eq!(rb.physical_to_logical(0), Ok(1));
+        assert_eq!(rb.physical_to_logical(3), Ok(0));
+        for index in 0..4 {
+            let physical = rb.logical_to_physical(index).unwrap();
+            assert_eq!(rb.storage()[physical], *rb.get(index));
+        }
+        let err = IndexError { index: 4, len: 4 };
+        assert_eq!(rb.try_set_first(4), Err(err));
+        assert_eq!(rb.logical_to_physical(4), Err(err));
+        assert_eq!(rb.physical_to_logical(4), Err(err));
+        assert_eq!(rb.first(), 3);
+    }
+
+    #[test]
+    fn test_eq_ignore_rotation() {
+        let a = Fixed::from([1, 2, 1, 2, 3]);
+        let mut b = Fixed::from(vec![2, 3, 1, 2, 1]);
+        assert!(a.eq_ignore_rotation(&b));
+        b.push(4);
+        assert!(!a.eq_ignore_rotation(&b));
+        assert!(!a.eq_ignore_rotation(&Fixed::from([1, 2])));
+        assert!(Fixed::from([7]).eq_ignore_rotation(&Fixed::from([7])));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let mut a = Fixed::from([3, 1, 2, 1, 2]);
+        assert_eq!(a.canonicalize(), 1);
+        assert!(a.iter().eq(&[1, 2, 1, 2, 3]));
+        let mut b = Fixed::from([2, 1, 2, 3, 1]);
+        b.canonicalize();
+        assert!(a.iter().eq(b.iter()));
+
+        let mut same = Fixed::from([5, 5, 5]);
+        same.rotate_left(1);
+        same.canonicalize();
+        assert!(same.iter().eq(&[5, 5, 5]));
+    }
+
+    #[test]
+    fn test_wrapping_helpers() {
+        for len in [1, 2, 3, 4, 7, 8] {
+            for a in 0..len {
+                assert_eq!(wrapping_increment(a, len), (a + 1) % len);
+                for b in 0..len {
+                    assert_eq!(add_mod(a, b, len), (a + b) % len);
+                    assert_eq!(sub_mod(a, b, len), (a + len - b) % len);
+                }
+            }
+        }
+        assert_eq!(
+            add_mod(usize::MAX - 1, usize::MAX - 1, usize::MAX),
+            usize::MAX - 2
+        );
+        assert_eq!(wrapping_increment(usize::MAX - 1, usize::MAX), 0);
+
+        let mut rb = Fixed::from([0, 1, 2, 3]);
+        rb.set_first(3);
+        assert_eq!(*rb.get(usize::MAX), 2);
+    }
+
+    #[test]
+    fn test_bulk_copies() {
+        let mut rb = Fixed::from([0u8; 5]);
+        rb.extend_from_slice(b"abc");
+        rb.extend_from_slice(b"de");
+        rb.extend_from_slice(b"fg");
+        assert!(rb.iter().eq(b"cdefg"));
+        rb.extend_from_slice(b"0123456");
+        assert!(rb.iter().eq(b"23456"));
+        rb.extend_from_slice(b"");
+
+        rb.push(b'7');
+        rb.copy_in(3, b"xy");
+        let mut out = [0; 5];
+        rb.copy_to_slice(&mut out);
+        assert_eq!(&out, b"345xy");
+        let mut out = [0; 3];
+        rb.copy_out(2, &mut out);
+        assert_eq!(&out, b"5xy");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_copy_in_out_of_bounds() {
+        Fixed::from([0u8; 3]).copy_in(2, b"ab");
+    }
+
+    #[test]
+    fn test_get_out_of_range() {
+        let rb = Fixed::from([0i32; 3]);
+        let _ = rb[10];
+    }
+
+    #[test]
+    fn test_try_from_iter() {
+        let rb = Fixed::<[u32; 3]>::try_from_iter(1..).unwrap();
+        assert!(rb.iter().eq(&[1, 2, 3]));
+        assert_eq!(
+            Fixed::<[String; 3]>::try_from_iter(["a".to_string()]).unwrap_err(),
+            TooFewElements {
+                count: 1,
+                expected: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_latest_from_iter() {
+        let rb = Fixed::<[u64; 3]>::latest_from_iter(0..u64::MAX).unwrap();
+        assert!(rb.iter().eq(&[u64::MAX - 3, u64::MAX - 2, u64::MAX - 1]));
+        // No size hint to skip by.
+        let rb = Fixed::<[u32; 2]>::latest_from_iter((1..=5).filter(|x| x % 2 == 1)).unwrap();
+        assert!(rb.iter().eq(&[3, 5]));
+        assert_eq!(
+            Fixed::<[u32; 3]>::latest_from_iter([1, 2]).unwrap_err(),
+            TooFewElements {
+                count: 2,
+                expected: 3
+            }
+        );
+
+        let rb = Fixed::<Vec<_>>::latest_from_iter("a long line".split(' '), 1);
+        assert!(rb.iter().eq(&["line"]));
+        let rb = Fixed::<Vec<_>>::latest_from_iter(0..2, 5);
+        assert!(rb.iter().eq(&[0, 1]));
+    }
+
+    #[test]
+    fn test_extend_skips_overwritten() {
+        let mut rb = Fixed::from([0u64; 4]);
+        rb.extend(0..1_000_000_000);
+        assert!(rb
+            .iter()
+            .eq(&[999_999_996, 999_999_997, 999_999_998, 999_999_999]));
+        assert_eq!(rb.first(), 0);
+
+        let mut rb = Fixed::from([0; 3]);
+        rb.extend(0..5);
+        // Same layout as pushing one by one.
+        assert_eq!(rb.first(), 2);
+        assert_eq!(rb.storage(), &[3, 4, 2]);
+    }
+
+    #[test]
+    fn test_eq_across_storage() {
+        let mut array = Fixed::from([0u8; 3]);
+        array.extend([1, 2, 3, 4]);
+        let fixture = Fixed::from_raw_parts(1, vec![4, 2, 3]);
+        assert_eq!(array, fixture);
+        assert_eq!(fixture.clone().map_storage(Vec::into_boxed_slice), array);
+        let mut scratch = [4, 2, 3];
+        assert_eq!(array, Fixed::from_raw_parts(1, &mut scratch[..]));
+        // Equal elements, rotated differently.
+        assert_ne!(Fixed::from(&[2u8, 3, 4][..]), array);
+        assert_ne!(Fixed::from(vec![0u8; 2]), Fixed::from([0u8; 3]));
+    }
+
+    #[test]
+    fn test_borrowed_to_owned() {
+        let mut scratch = [0u32; 3];
+        let owned = {
+            let mut rb = Fixed::from(&mut scratch[..]);
+            rb.extend([1, 2, 3, 4]);
+            rb.to_vec()
+        };
+        assert_eq!(owned.first(), 1);
+        assert!(owned.iter().eq(&[2, 3, 4]));
+
+        let borrowed = Fixed::from_raw_parts(2, &scratch[..]);
+        let boxed = borrowed.to_boxed();
+        assert_eq!(boxed, borrowed);
+        assert!(boxed.iter().eq(&[3, 4, 2]));
+    }
+
+    #[test]
+    fn test_array_boxed_round_trip() {
+        let mut rb = Fixed::from([0u32; 3]);
+        rb.extend([1, 2, 3, 4]);
+        let boxed = rb.into_boxed();
+        assert_eq!(boxed, rb);
+        let boxed = boxed.try_into_array::<2>().unwrap_err();
+        assert_eq!(boxed.try_into_array::<3>().unwrap(), rb);
+    }
+
+    #[test]
+    fn test_get_many_mut() {
+        let mut rb = Fixed::from([0; 4]);
+        rb.extend([1, 2, 3, 4, 5]);
+        let [oldest, newest] = rb.get_many_mut([0, 3]).unwrap();
+        mem::swap(oldest, newest);
+        assert!(rb.iter().eq(&[5, 3, 4, 2]));
+        // Index 4 wraps to 0.
+        assert!(rb.get_many_mut([0, 4]).is_none());
+        assert!(rb.get_many_mut([]).is_some());
+    }
+
+    #[test]
+    fn test_get_batch() {
+        let mut rb = Fixed::from([0; 5]);
+        rb.extend(1..=7);
+        // Logical order 3..=7, physically split after 5.
+        assert_eq!(rb.get_batch::<3>(0), Some([&3, &4, &5]));
+        assert_eq!(rb.get_batch::<3>(1), Some([&4, &5, &6]));
+        assert_eq!(rb.get_batch::<2>(3), Some([&6, &7]));
+        assert_eq!(rb.get_batch::<2>(4), None);
+        assert_eq!(rb.get_batch::<0>(5), Some([]));
+        assert_eq!(rb.get_batch::<1>(usize::MAX), None);
+    }
+}
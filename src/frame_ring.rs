@@ -0,0 +1,264 @@
+//! Interleaved multichannel frames, the layout audio buffers use.
+
+use core::array;
+use core::iter::FusedIterator;
+
+use super::fixed::Fixed;
+use super::traits::{Slice, SliceMut};
+
+/// Ring of interleaved frames of `C` channels over flat storage.
+///
+/// Frame `i` is the `C` consecutive samples starting at logical index
+/// `i * C`. The storage length must be a multiple of `C` and frames are only
+/// pushed whole, so a frame never crosses the physical end of the storage.
+#[derive(Clone, Debug)]
+pub struct FrameRing<S, const C: usize> {
+    ring: Fixed<S>,
+}
+
+impl<S, const C: usize> FrameRing<S, C>
+where
+    S: Slice,
+{
+    /// Wraps `ring` as frames of `C` samples.
+    ///
+    /// Panics if `C` is zero, or if the length or `first` of `ring` is not a
+    /// multiple of `C`.
+    pub fn new(ring: Fixed<S>) -> Self {
+        assert!(C > 0, "frames need at least one channel");
+        assert_eq!(ring.len() % C, 0, "length is not a whole number of frames");
+        assert_eq!(ring.first() % C, 0, "first is not at a frame boundary");
+        FrameRing { ring }
+    }
+
+    #[inline]
+    pub fn channels(&self) -> usize {
+        C
+    }
+
+    /// Number of frames.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ring.len() / C
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Frame at logical `index`, oldest first. Wraps like `Fixed::get`.
+    #[inline]
+    pub fn frame(&self, index: usize) -> &[S::Element; C] {
+        let start = index % self.len() * C;
+        let (frame, _) = self.ring.range(start..start + C).as_slices();
+        frame.try_into().unwrap()
+    }
+
+    /// Frames, oldest first.
+    #[inline]
+    pub fn frames(&self) -> impl ExactSizeIterator<Item = &[S::Element; C]> + '_ {
+        (0..self.len()).map(move |index| self.frame(index))
+    }
+
+    /// Samples of one channel. Panics if `channel >= C`.
+    #[inline]
+    pub fn channel(&self, channel: usize) -> Channel<'_, S, C> {
+        assert!(channel < C, "channel out of bounds");
+        Channel {
+            ring: &self.ring,
+            channel,
+        }
+    }
+
+    /// One view per channel, in channel order.
+    #[inline]
+    pub fn split_channels(&self) -> [Channel<'_, S, C>; C] {
+        array::from_fn(|channel| self.channel(channel))
+    }
+
+    /// The underlying ring of samples.
+    #[inline]
+    pub fn as_fixed(&self) -> &Fixed<S> {
+        &self.ring
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Fixed<S> {
+        self.ring
+    }
+}
+
+impl<S, const C: usize> FrameRing<S, C>
+where
+    S: SliceMut,
+    S::Element: Copy,
+{
+    /// Pushes `frame` as the newest frame, overwriting the oldest one.
+    #[inline]
+    pub fn push_frame(&mut self, frame: &[S::Element; C]) {
+        self.ring.extend_from_slice(frame);
+    }
+}
+
+/// Samples of one channel of a `FrameRing`, oldest first.
+#[derive(Debug)]
+pub struct Channel<'a, S, const C: usize> {
+    ring: &'a Fixed<S>,
+    channel: usize,
+}
+
+impl<S, const C: usize> Clone for Channel<'_, S, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S, const C: usize> Copy for Channel<'_, S, C> {}
+
+impl<'a, S, const C: usize> Channel<'a, S, C>
+where
+    S: Slice,
+{
+    /// Number of samples, one per frame.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ring.len() / C
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn channel(&self) -> usize {
+        self.channel
+    }
+
+    /// Sample of frame `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'a S::Element> {
+        let ring: &'a Fixed<S> = self.ring;
+        (index < self.len()).then(|| ring.get(index * C + self.channel))
+    }
+
+    #[inline]
+    pub fn iter(&self) -> ChannelIter<'a, S, C> {
+        ChannelIter {
+            channel: *self,
+            front: 0,
+            back: self.len(),
+        }
+    }
+}
+
+impl<'a, S, const C: usize> IntoIterator for Channel<'a, S, C>
+where
+    S: Slice,
+{
+    type Item = &'a S::Element;
+    type IntoIter = ChannelIter<'a, S, C>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the samples of one channel.
+#[derive(Debug)]
+pub struct ChannelIter<'a, S, const C: usize> {
+    channel: Channel<'a, S, C>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, S, const C: usize> Iterator for ChannelIter<'a, S, C>
+where
+    S: Slice,
+{
+    type Item = &'a S::Element;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.channel.get(self.front - 1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<S, const C: usize> DoubleEndedIterator for ChannelIter<'_, S, C>
+where
+    S: Slice,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.channel.get(self.back)
+    }
+}
+
+impl<S, const C: usize> ExactSizeIterator for ChannelIter<'_, S, C> where S: Slice {}
+
+impl<S, const C: usize> FusedIterator for ChannelIter<'_, S, C> where S: Slice {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push_and_frames() {
+        let mut frames: FrameRing<_, 2> = FrameRing::new(Fixed::from([0i16; 6]));
+        assert_eq!(frames.len(), 3);
+        for i in 1..=4 {
+            frames.push_frame(&[i, -i]);
+        }
+        assert_eq!(frames.as_fixed().first(), 2);
+        assert_eq!(frames.frame(0), &[2, -2]);
+        assert_eq!(frames.frame(4), &[3, -3]);
+        assert!(frames.frames().eq([&[2, -2], &[3, -3], &[4, -4]]));
+        assert_eq!(frames.into_inner().storage(), &[4, -4, 2, -2, 3, -3]);
+    }
+
+    #[test]
+    fn test_channels() {
+        let mut frames: FrameRing<_, 3> = FrameRing::new(Fixed::from(vec![0u8; 6]));
+        for i in 1..=3 {
+            frames.push_frame(&[i, 10 * i, 100 + i]);
+        }
+        let [left, center, right] = frames.split_channels();
+        assert!(left.iter().eq(&[2, 3]));
+        assert!(center.iter().rev().eq(&[30, 20]));
+        assert_eq!(right.get(1), Some(&103));
+        assert_eq!(right.get(2), None);
+        assert_eq!(frames.channel(1).channel(), 1);
+        assert_eq!(frames.channel(2).into_iter().len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_partial_frame() {
+        FrameRing::<_, 2>::new(Fixed::from([0; 5]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_misaligned_first() {
+        let mut ring = Fixed::from([0; 4]);
+        ring.push(1);
+        FrameRing::<_, 2>::new(ring);
+    }
+}
@@ -0,0 +1,191 @@
+//! Per-slot generation counters, so references kept outside a ring can tell
+//! when their element was overwritten.
+
+use super::fixed::Fixed;
+use super::traits::{Slice, SliceMut};
+
+/// An element together with the number of times its slot was overwritten.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Versioned<T> {
+    writes: u64,
+    value: T,
+}
+
+impl<T> Versioned<T> {
+    /// An element in a slot that was never written.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Versioned { writes: 0, value }
+    }
+
+    #[inline]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    #[inline]
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for Versioned<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Identifies what one slot of a `Generational` ring held at one time.
+///
+/// It names the physical slot rather than the logical index, which shifts
+/// with every push.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Generation {
+    slot: usize,
+    writes: u64,
+}
+
+/// `Fixed` ring whose slots count how often they were overwritten.
+///
+/// `get_versioned` hands out a `Generation` along with an element, and
+/// `is_current` later tells whether the element is still there.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Generational<S> {
+    ring: Fixed<S>,
+}
+
+impl<S, T> Generational<S>
+where
+    S: Slice<Element = Versioned<T>>,
+{
+    /// Wraps `storage`, keeping the generations it holds.
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Generational {
+            ring: Fixed::from(storage),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// Element at logical `index`, which wraps like in `Fixed::get`.
+    #[inline]
+    pub fn get(&self, index: usize) -> &T {
+        &self.ring.get(index).value
+    }
+
+    /// Element at logical `index` and the generation it belongs to.
+    #[inline]
+    pub fn get_versioned(&self, index: usize) -> (Generation, &T) {
+        let slot = self.ring.logical_to_physical(index % self.len()).unwrap();
+        let entry = &self.ring.storage().slice()[slot];
+        let generation = Generation {
+            slot,
+            writes: entry.writes,
+        };
+        (generation, &entry.value)
+    }
+
+    /// Whether the element of `generation` was not overwritten since.
+    #[inline]
+    pub fn is_current(&self, generation: Generation) -> bool {
+        self.get_current(generation).is_some()
+    }
+
+    /// The element of `generation`, unless it was overwritten since.
+    #[inline]
+    pub fn get_current(&self, generation: Generation) -> Option<&T> {
+        self.ring
+            .storage()
+            .slice()
+            .get(generation.slot)
+            .filter(|entry| entry.writes == generation.writes)
+            .map(|entry| &entry.value)
+    }
+
+    /// Elements, oldest first.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T> + 'a
+    where
+        T: 'a,
+    {
+        self.ring.iter().map(|entry| &entry.value)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Fixed<S> {
+        self.ring
+    }
+}
+
+impl<S, T> Generational<S>
+where
+    S: SliceMut<Element = Versioned<T>>,
+{
+    /// Pushes `item` and returns the evicted element, whose generations are
+    /// no longer current.
+    #[inline]
+    pub fn push(&mut self, item: T) -> T {
+        let writes = self.ring.get(0).writes + 1;
+        self.ring
+            .push(Versioned {
+                writes,
+                value: item,
+            })
+            .value
+    }
+
+    /// Mutable access to the element at logical `index`. The generation
+    /// stays the same, as the element is still the same one.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut T {
+        &mut self.ring.get_mut(index).value
+    }
+}
+
+impl<S, T> Extend<T> for Generational<S>
+where
+    S: SliceMut<Element = Versioned<T>>,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generations() {
+        let mut ring = Generational::new([Versioned::new(0); 3]);
+        ring.push(1);
+        let (one, value) = ring.get_versioned(2);
+        assert_eq!(*value, 1);
+        let (zero, _) = ring.get_versioned(0);
+        ring.push(2);
+        assert!(ring.is_current(one));
+        assert!(!ring.is_current(zero));
+        *ring.get_mut(1) = 10;
+        assert_eq!(ring.get_current(one), Some(&10));
+
+        ring.extend([3, 4]);
+        assert!(ring.iter().eq(&[2, 3, 4]));
+        assert!(!ring.is_current(one));
+        // The slot of `one` now holds 4.
+        let (four, _) = ring.get_versioned(2);
+        assert_ne!(four, one);
+        assert_eq!(ring.get_versioned(5).0, four);
+    }
+}
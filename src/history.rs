@@ -0,0 +1,267 @@
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// A recorded edit and whether it starts an undo step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Entry<T> {
+    edit: T,
+    starts_step: bool,
+}
+
+/// Undo/redo history of edits, grouped into steps.
+///
+/// The newest entry lives at the last logical index. Applied entries come
+/// first and undone ones after them, until a new edit discards the undone
+/// ones. When the history is full, recording evicts the oldest step as a
+/// whole; a single group that outgrows the capacity loses its older edits.
+#[derive(Clone, Debug)]
+pub struct History<S> {
+    entries: Fixed<S>,
+    stored: usize,
+    undone: usize,
+    in_group: bool,
+    group_empty: bool,
+}
+
+impl<S, T> History<S>
+where
+    S: SliceMut<Element = Option<Entry<T>>>,
+{
+    /// Creates a history over `storage`, which is cleared.
+    pub fn new(storage: S) -> Self {
+        let mut entries = Fixed::from(storage);
+        entries.iter_mut().for_each(|entry| *entry = None);
+        History {
+            entries,
+            stored: 0,
+            undone: 0,
+            in_group: false,
+            group_empty: false,
+        }
+    }
+
+    /// Number of edits the history can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        self.stored > self.undone
+    }
+
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        self.undone > 0
+    }
+
+    /// Starts a group: every edit recorded until `end_group` is undone and
+    /// redone as one step.
+    #[inline]
+    pub fn begin_group(&mut self) {
+        self.in_group = true;
+        self.group_empty = true;
+    }
+
+    #[inline]
+    pub fn end_group(&mut self) {
+        self.in_group = false;
+    }
+
+    /// Records `edit` as applied, discarding every undone edit.
+    pub fn record(&mut self, edit: T) {
+        self.discard_undone();
+        let starts_step = !self.in_group || self.group_empty;
+        self.group_empty = false;
+        let evicted = self.entries.push(Some(Entry { edit, starts_step }));
+        if evicted.is_some() {
+            self.evict_partial_step();
+        } else {
+            self.stored += 1;
+        }
+    }
+
+    /// Marks the newest applied step as undone and returns its edits in the
+    /// order they were recorded; revert them in reverse. Ends an open group.
+    pub fn undo<'a>(&'a mut self) -> Option<impl DoubleEndedIterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        self.end_group();
+        if !self.can_undo() {
+            return None;
+        }
+        let end = self.capacity() - self.undone;
+        let oldest = self.capacity() - self.stored;
+        let mut start = end - 1;
+        while start > oldest && !self.entry(start).starts_step {
+            start -= 1;
+        }
+        self.undone += end - start;
+        Some(self.edits(start, end))
+    }
+
+    /// Marks the oldest undone step as applied again and returns its edits in
+    /// the order they were recorded. Ends an open group.
+    pub fn redo<'a>(&'a mut self) -> Option<impl DoubleEndedIterator<Item = &'a T>>
+    where
+        T: 'a,
+    {
+        self.end_group();
+        if !self.can_redo() {
+            return None;
+        }
+        let start = self.capacity() - self.undone;
+        let mut end = start + 1;
+        while end < self.capacity() && !self.entry(end).starts_step {
+            end += 1;
+        }
+        self.undone -= end - start;
+        Some(self.edits(start, end))
+    }
+
+    /// Drops every edit and ends an open group.
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.stored = 0;
+        self.undone = 0;
+        self.in_group = false;
+    }
+
+    fn entry(&self, index: usize) -> &Entry<T> {
+        self.entries[index].as_ref().unwrap()
+    }
+
+    fn edits<'a>(&'a self, start: usize, end: usize) -> impl DoubleEndedIterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        self.entries
+            .range(start..end)
+            .into_iter()
+            .map(|entry| &entry.as_ref().unwrap().edit)
+    }
+
+    fn discard_undone(&mut self) {
+        let len = self.capacity();
+        for index in len - self.undone..len {
+            self.entries[index] = None;
+        }
+        self.entries.rotate_right(self.undone);
+        self.stored -= self.undone;
+        self.undone = 0;
+    }
+
+    /// Drops what is left of the step whose first edit was just evicted,
+    /// unless that step is the newest one: a group that outgrows the capacity
+    /// only loses the evicted edit.
+    fn evict_partial_step(&mut self) {
+        let len = self.capacity();
+        let oldest = len - self.stored;
+        if (oldest..len).all(|index| !self.entry(index).starts_step) {
+            if let Some(entry) = &mut self.entries[oldest] {
+                entry.starts_step = true;
+            }
+            return;
+        }
+        while !self.entry(len - self.stored).starts_step {
+            self.entries[len - self.stored] = None;
+            self.stored -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn undo(history: &mut History<impl SliceMut<Element = Option<Entry<u32>>>>) -> Vec<u32> {
+        history
+            .undo()
+            .map_or(vec![], |edits| edits.copied().collect())
+    }
+
+    fn redo(history: &mut History<impl SliceMut<Element = Option<Entry<u32>>>>) -> Vec<u32> {
+        history
+            .redo()
+            .map_or(vec![], |edits| edits.copied().collect())
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = History::new([None; 4]);
+        assert!(!history.can_undo());
+        history.record(1);
+        history.record(2);
+        assert_eq!(undo(&mut history), [2]);
+        assert_eq!(undo(&mut history), [1]);
+        assert!(undo(&mut history).is_empty());
+        assert_eq!(redo(&mut history), [1]);
+        assert!(history.can_redo());
+
+        history.record(3);
+        assert!(!history.can_redo());
+        assert_eq!(undo(&mut history), [3]);
+        assert_eq!(undo(&mut history), [1]);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut history = History::new(vec![None; 8]);
+        history.record(1);
+        history.begin_group();
+        history.record(2);
+        history.record(3);
+        history.record(4);
+        history.end_group();
+        history.record(5);
+
+        assert_eq!(undo(&mut history), [5]);
+        assert_eq!(undo(&mut history), [2, 3, 4]);
+        assert_eq!(redo(&mut history), [2, 3, 4]);
+        assert_eq!(undo(&mut history), [2, 3, 4]);
+        assert_eq!(undo(&mut history), [1]);
+        assert_eq!(redo(&mut history), [1]);
+        assert_eq!(redo(&mut history), [2, 3, 4]);
+        assert_eq!(redo(&mut history), [5]);
+        assert!(redo(&mut history).is_empty());
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut history = History::new([None; 4]);
+        history.begin_group();
+        history.record(1);
+        history.record(2);
+        history.end_group();
+        history.record(3);
+        history.record(4);
+        // Evicting 1 takes the rest of its group along.
+        history.record(5);
+        assert_eq!(undo(&mut history), [5]);
+        assert_eq!(undo(&mut history), [4]);
+        assert_eq!(undo(&mut history), [3]);
+        assert!(undo(&mut history).is_empty());
+
+        history.clear();
+        history.begin_group();
+        for edit in 1..=6 {
+            history.record(edit);
+        }
+        assert_eq!(undo(&mut history), [3, 4, 5, 6]);
+        assert!(!history.can_undo());
+
+        history.clear();
+        history.begin_group();
+        for edit in 1..=5 {
+            history.record(edit);
+        }
+        history.end_group();
+        history.record(6);
+        // The rest of the group goes once its first edit is evicted.
+        assert_eq!(undo(&mut history), [6]);
+        assert!(!history.can_undo());
+    }
+}
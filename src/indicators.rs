@@ -0,0 +1,296 @@
+//! Technical indicators over a sliding window of prices.
+
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// Running sum and sum of squares of the newest `len` samples.
+///
+/// The sums are taken of the distances to `shift`, a recent sample, so the
+/// variance does not cancel away when the samples are large and close
+/// together. They are updated incrementally and recomputed from the window,
+/// with a new shift, once per lap, so rounding errors do not pile up.
+#[derive(Clone, Debug)]
+struct Moments<S> {
+    window: Fixed<S>,
+    filled: usize,
+    shift: f64,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl<S> Moments<S>
+where
+    S: SliceMut<Element = f64>,
+{
+    fn new(storage: S) -> Self {
+        let mut window = Fixed::from(storage);
+        window.fill(0.0);
+        Moments {
+            window,
+            filled: 0,
+            shift: 0.0,
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+
+    fn push(&mut self, x: f64) {
+        if self.filled == 0 {
+            self.shift = x;
+        }
+        let evicted = self.window.push(x) - self.shift;
+        if self.is_full() {
+            self.sum -= evicted;
+            self.sum_sq -= evicted * evicted;
+        } else {
+            self.filled += 1;
+        }
+        let d = x - self.shift;
+        self.sum += d;
+        self.sum_sq += d * d;
+        if self.window.first() == 0 {
+            let shift = self.window[0];
+            self.shift = shift;
+            self.sum = self.window.iter().map(|x| x - shift).sum();
+            self.sum_sq = self.window.iter().map(|x| (x - shift) * (x - shift)).sum();
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.filled == self.window.len()
+    }
+
+    fn mean(&self) -> f64 {
+        self.shift + self.sum / self.filled as f64
+    }
+
+    /// Population standard deviation.
+    fn std_dev(&self) -> f64 {
+        let mean = self.sum / self.filled as f64;
+        (self.sum_sq / self.filled as f64 - mean * mean)
+            .max(0.0)
+            .sqrt()
+    }
+}
+
+/// Bollinger bands: the moving average and `k` standard deviations either
+/// side of it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bands {
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+}
+
+/// Bollinger bands over the last `len` prices.
+#[derive(Clone, Debug)]
+pub struct Bollinger<S> {
+    moments: Moments<S>,
+    k: f64,
+}
+
+impl<S> Bollinger<S>
+where
+    S: SliceMut<Element = f64>,
+{
+    /// Bands `k` standard deviations wide over a window the length of
+    /// `storage`, which is cleared.
+    #[inline]
+    pub fn new(storage: S, k: f64) -> Self {
+        Bollinger {
+            moments: Moments::new(storage),
+            k,
+        }
+    }
+
+    /// Adds `price`; returns the bands once the window is full.
+    pub fn push(&mut self, price: f64) -> Option<Bands> {
+        self.moments.push(price);
+        self.moments.is_full().then(|| {
+            let middle = self.moments.mean();
+            let width = self.k * self.moments.std_dev();
+            Bands {
+                lower: middle - width,
+                middle,
+                upper: middle + width,
+            }
+        })
+    }
+}
+
+/// Relative strength index over the last `len` price changes, using simple
+/// averages of the gains and losses.
+#[derive(Clone, Debug)]
+pub struct Rsi<S> {
+    changes: Fixed<S>,
+    filled: usize,
+    gains: f64,
+    losses: f64,
+    last: Option<f64>,
+}
+
+impl<S> Rsi<S>
+where
+    S: SliceMut<Element = f64>,
+{
+    /// RSI over as many changes as `storage` holds; it is cleared.
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        let mut changes = Fixed::from(storage);
+        changes.fill(0.0);
+        Rsi {
+            changes,
+            filled: 0,
+            gains: 0.0,
+            losses: 0.0,
+            last: None,
+        }
+    }
+
+    /// Adds `price`; returns the index, between 0 and 100, once the window
+    /// is full. A window without any change is neutral at 50.
+    pub fn push(&mut self, price: f64) -> Option<f64> {
+        let change = price - self.last.replace(price)?;
+        let evicted = self.changes.push(change);
+        if self.filled == self.changes.len() {
+            self.gains -= evicted.max(0.0);
+            self.losses -= (-evicted).max(0.0);
+        } else {
+            self.filled += 1;
+        }
+        self.gains += change.max(0.0);
+        self.losses += (-change).max(0.0);
+        if self.changes.first() == 0 {
+            self.gains = self.changes.iter().map(|c| c.max(0.0)).sum();
+            self.losses = self.changes.iter().map(|c| (-c).max(0.0)).sum();
+        }
+
+        if self.filled < self.changes.len() {
+            return None;
+        }
+        Some(match (self.gains > 0.0, self.losses > 0.0) {
+            (false, false) => 50.0,
+            (_, false) => 100.0,
+            _ => 100.0 - 100.0 / (1.0 + self.gains / self.losses),
+        })
+    }
+}
+
+/// How many standard deviations each value lies from the mean of the last
+/// `len` values, itself included.
+#[derive(Clone, Debug)]
+pub struct ZScore<S> {
+    moments: Moments<S>,
+}
+
+impl<S> ZScore<S>
+where
+    S: SliceMut<Element = f64>,
+{
+    /// Z-score over a window the length of `storage`, which is cleared.
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        ZScore {
+            moments: Moments::new(storage),
+        }
+    }
+
+    /// Adds `x`; returns its z-score once the window is full. A window of
+    /// equal values scores 0.
+    pub fn push(&mut self, x: f64) -> Option<f64> {
+        self.moments.push(x);
+        if !self.moments.is_full() {
+            return None;
+        }
+        let std_dev = self.moments.std_dev();
+        if std_dev > 0.0 {
+            Some((x - self.moments.mean()) / std_dev)
+        } else {
+            Some(0.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_bollinger() {
+        let mut bands = Bollinger::new([0.0; 4], 2.0);
+        assert_eq!(bands.push(2.0), None);
+        assert_eq!(bands.push(4.0), None);
+        assert_eq!(bands.push(4.0), None);
+        // 2, 4, 4, 6: mean 4, population std dev sqrt(2).
+        let b = bands.push(6.0).unwrap();
+        assert!(close(b.middle, 4.0));
+        assert!(close(b.upper, 4.0 + 2.0 * 2f64.sqrt()));
+        assert!(close(b.lower, 4.0 - 2.0 * 2f64.sqrt()));
+        let b = bands.push(4.0).unwrap();
+        assert!(close(b.middle, 4.5));
+    }
+
+    #[test]
+    fn test_rsi() {
+        let mut rsi = Rsi::new(vec![0.0; 3]);
+        assert_eq!(rsi.push(10.0), None);
+        assert_eq!(rsi.push(11.0), None);
+        assert_eq!(rsi.push(11.0), None);
+        assert_eq!(rsi.push(12.0), Some(100.0));
+        // Changes +0, +1, -1.
+        assert!(close(rsi.push(11.0).unwrap(), 50.0));
+        // Changes +1, -1, -3: gains 1, losses 4.
+        assert!(close(rsi.push(8.0).unwrap(), 20.0));
+        assert_eq!(rsi.push(8.0).map(|r| r < 20.0), Some(true));
+
+        let mut flat = Rsi::new([0.0; 2]);
+        for _ in 0..3 {
+            flat.push(5.0);
+        }
+        assert_eq!(flat.push(5.0), Some(50.0));
+    }
+
+    #[test]
+    fn test_z_score() {
+        let mut z = ZScore::new([0.0; 4]);
+        for x in [1.0, 1.0, 1.0] {
+            assert_eq!(z.push(x), None);
+        }
+        assert_eq!(z.push(1.0), Some(0.0));
+        // 1, 1, 1, 5: mean 2, std dev sqrt(3).
+        assert!(close(z.push(5.0).unwrap(), 3.0 / 3f64.sqrt()));
+    }
+
+    #[test]
+    fn test_long_run_stays_exact() {
+        let mut z = ZScore::new(vec![0.0; 5]);
+        let mut last = None;
+        for i in 0..10_000 {
+            last = z.push(1e8 + (i % 5) as f64);
+        }
+        // The window is always a permutation of 1e8 + 0..5.
+        let expected = (4.0 - 2.0) / 2f64.sqrt();
+        assert!((last.unwrap() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_large_offset_small_spread() {
+        let prices = [1e6 + 0.001, 1e6 + 0.002, 1e6 + 0.003, 1e6 + 0.004];
+        let std_dev = 0.001 * 1.25f64.sqrt();
+        let mut bands = Bollinger::new([0.0; 4], 2.0);
+        let mut z = ZScore::new([0.0; 4]);
+        let mut last = (None, None);
+        for lap in 0..3 {
+            for price in prices {
+                last = (bands.push(price + lap as f64), z.push(price + lap as f64));
+            }
+        }
+        let b = last.0.unwrap();
+        assert!(((b.upper - b.middle) - 2.0 * std_dev).abs() < 1e-7);
+        assert!((last.1.unwrap() - 0.0015 / std_dev).abs() < 1e-4);
+    }
+}
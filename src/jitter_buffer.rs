@@ -0,0 +1,231 @@
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// Counters describing what a `JitterBuffer` did with the packets it was given.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct JitterStats {
+    /// Packets accepted into the buffer.
+    pub received: u64,
+    /// Packets handed out in order.
+    pub released: u64,
+    /// Packets that arrived after their sequence number was already released
+    /// or skipped.
+    pub late: u64,
+    /// Packets whose sequence number was already buffered.
+    pub duplicate: u64,
+    /// Sequence numbers skipped because their packet never arrived.
+    pub lost: u64,
+    /// Buffered packets discarded because a packet far ahead of the window
+    /// forced it forward.
+    pub dropped: u64,
+}
+
+/// Reorders packets keyed by sequence number.
+///
+/// Slot `i` of the ring holds the packet with sequence number `next + i`, so
+/// releasing the head is a plain `push(None)`. A packet is released once it is
+/// the head and the buffer has seen a sequence number at least `depth` ahead of
+/// it; a missing head is declared lost under the same condition.
+///
+/// Sequence numbers are 64-bit and never wrap; narrower wire sequence numbers
+/// (e.g. RTP's 16-bit ones) have to be extended before they are pushed.
+#[derive(Clone, Debug)]
+pub struct JitterBuffer<S> {
+    slots: Fixed<S>,
+    next: Option<u64>,
+    highest: u64,
+    depth: u64,
+    buffered: usize,
+    stats: JitterStats,
+}
+
+impl<S, T> JitterBuffer<S>
+where
+    S: SliceMut<Element = Option<T>>,
+{
+    /// Creates a buffer over `storage`, which is cleared.
+    ///
+    /// Panics if `depth` is not less than the length of `storage`.
+    pub fn new(storage: S, depth: usize) -> Self {
+        let mut slots = Fixed::from(storage);
+        assert!(depth < slots.len());
+        slots.iter_mut().for_each(|slot| *slot = None);
+        JitterBuffer {
+            slots,
+            next: None,
+            highest: 0,
+            depth: depth as u64,
+            buffered: 0,
+            stats: JitterStats::default(),
+        }
+    }
+
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth as usize
+    }
+
+    /// Number of packets currently held.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buffered
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buffered == 0
+    }
+
+    /// Sequence number of the next packet to be released.
+    #[inline]
+    pub fn next_seq(&self) -> Option<u64> {
+        self.next
+    }
+
+    #[inline]
+    pub fn stats(&self) -> &JitterStats {
+        &self.stats
+    }
+
+    /// Buffers `packet`. Returns `false` if it was late or a duplicate, in which
+    /// case it is dropped.
+    pub fn push(&mut self, seq: u64, packet: T) -> bool {
+        let next = *self.next.get_or_insert(seq);
+        if seq < next {
+            self.stats.late += 1;
+            return false;
+        }
+
+        let capacity = self.slots.len() as u64;
+        if seq - next >= capacity {
+            self.skip(seq - next - capacity + 1);
+        }
+
+        let offset = (seq - self.next.unwrap_or(seq)) as usize;
+        let slot = &mut self.slots[offset];
+        if slot.is_some() {
+            self.stats.duplicate += 1;
+            return false;
+        }
+
+        *slot = Some(packet);
+        self.buffered += 1;
+        self.highest = self.highest.max(seq);
+        self.stats.received += 1;
+        true
+    }
+
+    /// Releases the next packet in order once it has been held for `depth`.
+    pub fn pop(&mut self) -> Option<T> {
+        loop {
+            let next = self.next?;
+            if self.buffered == 0 || self.highest < next + self.depth {
+                return None;
+            }
+            if let Some(packet) = self.advance() {
+                return Some(packet);
+            }
+        }
+    }
+
+    /// Releases the next buffered packet regardless of `depth`, skipping over
+    /// missing sequence numbers.
+    pub fn flush(&mut self) -> Option<T> {
+        while self.buffered > 0 {
+            if let Some(packet) = self.advance() {
+                return Some(packet);
+            }
+        }
+        None
+    }
+
+    fn advance(&mut self) -> Option<T> {
+        let head = self.slots.push(None);
+        self.next = self.next.map(|next| next + 1);
+        match head {
+            Some(packet) => {
+                self.buffered -= 1;
+                self.stats.released += 1;
+                Some(packet)
+            }
+            None => {
+                self.stats.lost += 1;
+                None
+            }
+        }
+    }
+
+    fn skip(&mut self, n: u64) {
+        let capacity = self.slots.len() as u64;
+        for _ in 0..n.min(capacity) {
+            if self.slots.push(None).is_some() {
+                self.buffered -= 1;
+                self.stats.dropped += 1;
+            } else {
+                self.stats.lost += 1;
+            }
+        }
+        self.stats.lost += n.saturating_sub(capacity);
+        self.next = self.next.map(|next| next + n);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reorder() {
+        let mut jb = JitterBuffer::new([None; 8], 0);
+        assert!(jb.push(10, 'a'));
+        assert!(jb.push(12, 'c'));
+        assert!(jb.push(11, 'b'));
+        assert_eq!(jb.pop(), Some('a'));
+        assert_eq!(jb.pop(), Some('b'));
+        assert_eq!(jb.pop(), Some('c'));
+        assert_eq!(jb.pop(), None);
+        assert_eq!(jb.stats().released, 3);
+    }
+
+    #[test]
+    fn test_depth_holds_and_loses() {
+        let mut jb = JitterBuffer::new([None; 8], 2);
+        jb.push(0, 0);
+        jb.push(2, 2);
+        assert_eq!(jb.pop(), Some(0));
+        assert_eq!(jb.pop(), None);
+        jb.push(3, 3);
+        assert_eq!(jb.pop(), None);
+        jb.push(4, 4);
+        assert_eq!(jb.pop(), Some(2));
+        assert_eq!(jb.stats().lost, 1);
+        assert_eq!(jb.flush(), Some(3));
+        assert_eq!(jb.flush(), Some(4));
+        assert_eq!(jb.flush(), None);
+    }
+
+    #[test]
+    fn test_late_and_duplicate() {
+        let mut jb = JitterBuffer::new(vec![None; 4], 0);
+        jb.push(5, ());
+        assert!(!jb.push(5, ()));
+        jb.pop();
+        assert!(!jb.push(4, ()));
+        assert!(!jb.push(5, ()));
+        assert_eq!(jb.stats().duplicate, 1);
+        assert_eq!(jb.stats().late, 2);
+    }
+
+    #[test]
+    fn test_jump_ahead() {
+        let mut jb = JitterBuffer::new([None; 4], 3);
+        jb.push(0, 0);
+        jb.push(1, 1);
+        jb.push(10, 10);
+        assert_eq!(jb.next_seq(), Some(7));
+        assert_eq!(jb.stats().dropped, 2);
+        assert_eq!(jb.stats().lost, 5);
+        assert_eq!(jb.len(), 1);
+        assert_eq!(jb.flush(), Some(10));
+    }
+}
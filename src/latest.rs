@@ -0,0 +1,244 @@
+//! Latest-value cell for "only the newest reading matters" data, with async
+//! change notification.
+
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::fixed::Fixed;
+use super::Vec;
+
+/// Producer side of a latest-value cell: a ring of one slot that every `set`
+/// overwrites.
+///
+/// Dropping it closes the cell; watchers keep the last value, and waiting
+/// for a change fails.
+#[derive(Debug)]
+pub struct Latest<T> {
+    shared: Arc<Mutex<State<T>>>,
+}
+
+/// Consumer side of a latest-value cell. Each watcher tracks which version it
+/// has seen, so `changed` only completes for values it has not seen yet.
+#[derive(Debug)]
+pub struct Watcher<T> {
+    shared: Arc<Mutex<State<T>>>,
+    seen: u64,
+}
+
+#[derive(Debug)]
+struct State<T> {
+    value: Fixed<[Option<T>; 1]>,
+    version: u64,
+    closed: bool,
+    wakers: Vec<Waker>,
+}
+
+/// The `Latest` of a watcher was dropped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Closed;
+
+impl fmt::Display for Closed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("latest-value cell closed")
+    }
+}
+
+impl<T> Latest<T> {
+    /// An empty cell.
+    pub fn new() -> Self {
+        Latest {
+            shared: Arc::new(Mutex::new(State {
+                value: Fixed::from([None]),
+                version: 0,
+                closed: false,
+                wakers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Replaces the value and wakes every watcher waiting for a change.
+    pub fn set(&self, value: T) {
+        let wakers = {
+            let mut state = lock(&self.shared);
+            state.value.push_overwrite(Some(value));
+            state.version += 1;
+            core::mem::take(&mut state.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// A watcher that has already seen the current value.
+    pub fn watch(&self) -> Watcher<T> {
+        let seen = lock(&self.shared).version;
+        Watcher {
+            shared: Arc::clone(&self.shared),
+            seen,
+        }
+    }
+
+    #[inline]
+    pub fn get_latest(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        lock(&self.shared).value[0].clone()
+    }
+}
+
+impl<T> Default for Latest<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Latest<T> {
+    fn drop(&mut self) {
+        let wakers = {
+            let mut state = lock(&self.shared);
+            state.closed = true;
+            core::mem::take(&mut state.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<T> Watcher<T> {
+    /// The current value, marking it as seen.
+    pub fn get_latest(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let state = lock(&self.shared);
+        self.seen = state.version;
+        state.value[0].clone()
+    }
+
+    /// Whether a value was set since this watcher last looked.
+    #[inline]
+    pub fn has_changed(&self) -> bool {
+        lock(&self.shared).version != self.seen
+    }
+
+    /// Completes once a value this watcher has not seen is set, or fails
+    /// once the `Latest` is dropped without one.
+    #[inline]
+    pub fn changed(&mut self) -> Changed<'_, T> {
+        Changed { watcher: self }
+    }
+
+    /// Poll form of `changed`, for building other futures and streams on a
+    /// watcher.
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+        let mut state = lock(&self.shared);
+        if state.version != self.seen {
+            self.seen = state.version;
+            return Poll::Ready(Ok(()));
+        }
+        if state.closed {
+            return Poll::Ready(Err(Closed));
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Clone for Watcher<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Watcher {
+            shared: Arc::clone(&self.shared),
+            seen: self.seen,
+        }
+    }
+}
+
+/// Future returned by `Watcher::changed`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Changed<'a, T> {
+    watcher: &'a mut Watcher<T>,
+}
+
+impl<T> Future for Changed<'_, T> {
+    type Output = Result<(), Closed>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().watcher.poll_changed(cx)
+    }
+}
+
+/// Locks the state; a panic while it was held cannot leave it inconsistent,
+/// so poisoning is ignored.
+fn lock<T>(shared: &Mutex<State<T>>) -> MutexGuard<'_, State<T>> {
+    shared
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+    use std::time::Duration;
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn test_get_latest() {
+        let latest = Latest::new();
+        let mut watcher = latest.watch();
+        assert_eq!(watcher.get_latest(), None);
+        latest.set(1);
+        latest.set(2);
+        assert!(watcher.has_changed());
+        assert_eq!(watcher.get_latest(), Some(2));
+        assert!(!watcher.has_changed());
+        assert_eq!(latest.get_latest(), Some(2));
+    }
+
+    #[test]
+    fn test_changed() {
+        let latest = Latest::new();
+        let mut watcher = latest.watch();
+        let mut other = watcher.clone();
+
+        let producer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            latest.set("reading");
+            latest
+        });
+        assert_eq!(block_on(watcher.changed()), Ok(()));
+        assert_eq!(watcher.get_latest(), Some("reading"));
+        assert_eq!(block_on(other.changed()), Ok(()));
+
+        drop(producer.join().unwrap());
+        assert_eq!(block_on(watcher.changed()), Err(Closed));
+        assert_eq!(watcher.get_latest(), Some("reading"));
+    }
+}
@@ -1 +1,98 @@
+//! Fixed-length ring buffers and the tools built on them.
+//!
+//! `Fixed` is the core ring; the other modules wrap it for counting, eviction
+//! hooks, time windows, persistence and export. The `prelude` brings the
+//! common types and traits into scope.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use std::boxed::Box;
+
+#[cfg(kani)]
+mod proofs;
+mod search;
+mod slot;
+mod traits;
+
+pub mod builder;
+pub mod candles;
+pub mod capture;
+pub mod checksum;
+#[cfg(feature = "std")]
+pub mod circular_file;
+pub mod clock;
+pub mod combine;
+pub mod copy_ext;
+pub mod counted;
+pub mod cursor;
+pub mod delta;
+pub mod dump;
+pub mod error;
+pub mod evict;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filling;
+pub mod fixed;
+pub mod frame_ring;
+pub mod generational;
+pub mod history;
+#[cfg(feature = "std")]
+pub mod indicators;
+pub mod jitter_buffer;
+#[cfg(feature = "std")]
+pub mod latest;
+#[doc(hidden)]
+pub mod macros;
+#[cfg(any(test, feature = "model-tests"))]
+pub mod model;
+#[cfg(feature = "std")]
+pub mod panic_dump;
+#[cfg(feature = "persistent")]
+pub mod persistent;
+pub mod prelude;
+pub mod prioritized;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod queue;
+pub mod rate_limiter;
+pub mod record_ring;
+#[cfg(feature = "std")]
+pub mod recorder;
+pub mod resample;
+#[cfg(feature = "log")]
+pub mod ring_logger;
+pub mod ring_slice;
+#[deprecated(note = "use the top-level modules, e.g. `rust_ringbuf::fixed`")]
 pub mod ringbuffer_1;
+pub mod rollback;
+pub mod sampling;
+pub mod scrollback;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod small_ring;
+pub mod string_ring;
+#[cfg(feature = "tracing")]
+pub mod tracing_layer;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "stream")]
+pub mod window_stream;
+pub mod windowed_counter;
+pub mod writer;
+
+pub use builder::FixedBuilder;
+pub use fixed::Fixed;
+pub use traits::{FromElements, Slice, SliceMut};
@@ -0,0 +1,60 @@
+use super::fixed::Fixed;
+use super::Box;
+
+/// Creates a `Fixed` ring buffer from its initial elements.
+///
+/// - `ringbuf![x; 256]`, with a literal length, stores the elements in an
+///   array, like `[x; 256]`.
+/// - `ringbuf![x; n]`, with any other length expression, stores `n` clones of
+///   `x` in a boxed slice and panics if `n` is zero.
+/// - `ringbuf![a, b, c]` stores the listed elements in an array.
+///
+/// Empty rings are rejected at compile time wherever the length is known.
+#[macro_export]
+macro_rules! ringbuf {
+    () => {
+        compile_error!("a ring buffer needs at least one element")
+    };
+    ($elem:expr; $n:literal) => {{
+        const _: () = assert!($n > 0, "a ring buffer needs at least one element");
+        $crate::fixed::Fixed::from([$elem; $n])
+    }};
+    ($elem:expr; $n:expr) => {
+        $crate::macros::from_elem($elem, $n)
+    };
+    ($($x:expr),+ $(,)?) => {
+        $crate::fixed::Fixed::from([$($x),+])
+    };
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn from_elem<T: Clone>(elem: T, n: usize) -> Fixed<Box<[T]>> {
+    Fixed::from(alloc::vec![elem; n].into_boxed_slice())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ringbuf() {
+        let mut rb: Fixed<[u8; 4]> = ringbuf![0u8; 4];
+        rb.push(1);
+        assert!(rb.iter().eq(&[0, 0, 0, 1]));
+
+        let n = 3;
+        let boxed: Fixed<Box<[String]>> = ringbuf![String::new(); n];
+        assert_eq!(boxed.len(), 3);
+
+        let listed: Fixed<[i32; 3]> = ringbuf![1, 2, 3,];
+        assert!(listed.iter().eq(&[1, 2, 3]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ringbuf_empty_runtime_len() {
+        let n = 0;
+        let _ = ringbuf![0u8; n];
+    }
+}
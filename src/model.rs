@@ -0,0 +1,239 @@
+//! Differential testing against a `VecDeque` reference model.
+//!
+//! `run` applies a random sequence of operations to a ring and to a
+//! `VecDeque` holding the same elements in logical order, and compares
+//! everything observable after every step. It panics at the first divergence
+//! with the seed, the step and the operation, so a failure can be replayed.
+
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use super::counted::Counted;
+use super::fixed::Fixed;
+use super::sampling::RandomSource;
+use super::traits::SliceMut;
+
+/// Operation applied to both the ring and the model.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    Push(u32),
+    PushOverwrite(u32),
+    Replace(usize, u32),
+    GetMut(usize, u32),
+    RotateLeft(usize),
+    RotateRight(usize),
+    SetFirst(usize),
+    /// Pushes through `as_view`.
+    ViewPush(u32),
+    /// `extend_from_slice` with `count` consecutive values from `start`.
+    ExtendFromSlice(u32, usize),
+}
+
+impl Op {
+    /// Draws an operation, with indices and shifts that often exceed `len` so
+    /// the wrapping paths are exercised.
+    pub fn random<R>(rng: &mut R, len: usize) -> Op
+    where
+        R: RandomSource + ?Sized,
+    {
+        let value = rng.next_u64() as u32;
+        let index = rng.below(3 * len + 1);
+        match rng.below(9) {
+            0 => Op::Push(value),
+            1 => Op::PushOverwrite(value),
+            2 => Op::Replace(index, value),
+            3 => Op::GetMut(index, value),
+            4 => Op::RotateLeft(index),
+            5 => Op::RotateRight(index),
+            6 => Op::SetFirst(index),
+            7 => Op::ExtendFromSlice(value, index),
+            _ => Op::ViewPush(value),
+        }
+    }
+
+    fn apply<S>(self, ring: &mut Fixed<S>, model: &mut VecDeque<u32>)
+    where
+        S: SliceMut<Element = u32>,
+    {
+        let len = model.len();
+        match self {
+            Op::Push(value) => {
+                let evicted = ring.push(value);
+                assert_eq!(Some(evicted), model.pop_front(), "evicted element");
+                model.push_back(value);
+            }
+            Op::PushOverwrite(value) => {
+                ring.push_overwrite(value);
+                model.pop_front();
+                model.push_back(value);
+            }
+            Op::Replace(index, value) => {
+                let old = ring.replace(index, value);
+                let slot = &mut model[index % len];
+                assert_eq!(old, *slot, "replaced element");
+                *slot = value;
+            }
+            Op::GetMut(index, value) => {
+                *ring.get_mut(index) = value;
+                model[index % len] = value;
+            }
+            Op::RotateLeft(n) => {
+                ring.rotate_left(n);
+                model.rotate_left(n % len);
+            }
+            Op::RotateRight(n) => {
+                ring.rotate_right(n);
+                model.rotate_right(n % len);
+            }
+            Op::SetFirst(index) => {
+                let shift = (index % len + len - ring.first()) % len;
+                ring.set_first(index);
+                model.rotate_left(shift);
+            }
+            Op::ExtendFromSlice(start, count) => {
+                let values: Vec<u32> = (0..count as u32).map(|i| start.wrapping_add(i)).collect();
+                ring.extend_from_slice(&values);
+                for value in values {
+                    model.pop_front();
+                    model.push_back(value);
+                }
+            }
+            Op::ViewPush(value) => {
+                ring.as_view().push(value);
+                model.pop_front();
+                model.push_back(value);
+            }
+        }
+    }
+}
+
+fn compare<S>(ring: &Fixed<S>, model: &VecDeque<u32>)
+where
+    S: SliceMut<Element = u32>,
+{
+    let len = model.len();
+    assert_eq!(ring.len(), len, "len");
+    assert!(ring.first() < len, "first out of bounds");
+    assert!(ring.iter().eq(model.iter()), "iter");
+    let (start, end) = ring.slices();
+    assert!(start.iter().chain(end).eq(model.iter()), "slices");
+    for index in 0..2 * len {
+        assert_eq!(*ring.get(index), model[index % len], "get({})", index);
+        assert_eq!(
+            *ring.get_back(index),
+            model[len - 1 - index % len],
+            "get_back({})",
+            index
+        );
+    }
+    for start in 0..=len {
+        let range = ring.range(start..);
+        assert!(range.iter().eq(model.range(start..)), "range({}..)", start);
+    }
+    assert!(ring.cursor().eq(model.iter()), "cursor");
+}
+
+/// Runs `steps` random operations from `seed` on `ring` and a model holding
+/// its current elements. Panics at the first divergence.
+pub fn run<S>(mut ring: Fixed<S>, seed: u64, steps: usize)
+where
+    S: SliceMut<Element = u32>,
+{
+    let mut model: VecDeque<u32> = ring.iter().copied().collect();
+    let mut rng = split_mix(seed);
+    let mut ops = Vec::with_capacity(steps);
+    for step in 0..steps {
+        let op = Op::random(&mut rng, model.len());
+        ops.push(op);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            op.apply(&mut ring, &mut model);
+            compare(&ring, &model);
+        }));
+        if result.is_err() {
+            panic!(
+                "ring diverged from the model: seed {}, step {}, ops {:?}",
+                seed, step, ops
+            );
+        }
+    }
+}
+
+/// Like `run` for `Counted`, also checking the sequence numbers against a
+/// count of the pushes.
+pub fn run_counted<S>(mut ring: Counted<S>, seed: u64, steps: usize)
+where
+    S: SliceMut<Element = u32>,
+{
+    let mut rng = split_mix(seed);
+    let mut pushed = ring.total_pushed();
+    for step in 0..steps {
+        let value = rng.next_u64() as u32;
+        if rng.below(2) == 0 {
+            ring.push(value);
+        } else {
+            ring.push_overwrite(value);
+        }
+        pushed += 1;
+        let len = ring.len() as u64;
+        assert_eq!(ring.total_pushed(), pushed, "seed {}, step {}", seed, step);
+        assert_eq!(ring.next_seq(), pushed, "seed {}, step {}", seed, step);
+        assert_eq!(
+            ring.get_by_seq(pushed - 1),
+            Some(&value),
+            "seed {}, step {}",
+            seed,
+            step
+        );
+        assert_eq!(ring.oldest_seq(), pushed.saturating_sub(len));
+        if pushed > len {
+            assert_eq!(ring.get_by_seq(pushed - len - 1), None);
+        }
+    }
+}
+
+/// SplitMix64, enough to spread seeds over the operation space.
+fn split_mix(seed: u64) -> impl FnMut() -> u64 {
+    let mut state = seed;
+    move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const STEPS: usize = 200;
+
+    #[test]
+    fn test_vec_and_box() {
+        for len in [1, 2, 3, 4, 5, 7, 8, 16] {
+            for seed in 0..40 {
+                let data: Vec<u32> = (0..len).collect();
+                run(Fixed::from(data.clone()), seed, STEPS);
+                run(Fixed::from(data.into_boxed_slice()), seed, STEPS);
+            }
+        }
+    }
+
+    #[test]
+    fn test_arrays() {
+        for seed in 0..40 {
+            run(Fixed::from([0u32; 1]), seed, STEPS);
+            run(Fixed::from([1u32, 2, 3]), seed, STEPS);
+            run(Fixed::from([0u32; 8]), seed, STEPS);
+        }
+    }
+
+    #[test]
+    fn test_counted() {
+        for seed in 0..20 {
+            run_counted(Counted::from([0u32; 3]), seed, STEPS);
+            run_counted(Counted::from(vec![0u32; 8]), seed, STEPS);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use core::fmt::Debug;
+use std::io::{self, Write};
+use std::panic;
+use std::sync::{Mutex, PoisonError, TryLockError};
+
+use super::fixed::Fixed;
+use super::recorder::Recorder;
+use super::traits::Slice;
+use super::Box;
+
+/// Ring contents that can be written out from a panic hook.
+pub trait PanicDump: Sync {
+    /// Writes the contents, oldest first, one entry per line.
+    fn dump_to(&self, out: &mut dyn Write) -> io::Result<()>;
+}
+
+impl<S> PanicDump for Mutex<Fixed<S>>
+where
+    S: Slice + Send,
+    S::Element: Debug,
+{
+    fn dump_to(&self, out: &mut dyn Write) -> io::Result<()> {
+        // The panicking thread may be the one holding the lock; never block.
+        let ring = match self.try_lock() {
+            Ok(ring) => ring,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => return writeln!(out, "<ring locked>"),
+        };
+        for item in ring.iter() {
+            writeln!(out, "{:?}", item)?;
+        }
+        Ok(())
+    }
+}
+
+impl PanicDump for Recorder {
+    fn dump_to(&self, out: &mut dyn Write) -> io::Result<()> {
+        // Same as above: the panicking thread may be recording.
+        let records = match self.try_dump() {
+            Some(records) => records,
+            None => return writeln!(out, "<ring locked>"),
+        };
+        for record in records {
+            writeln!(out, "{}", record)?;
+        }
+        Ok(())
+    }
+}
+
+/// Registers a panic hook that writes the contents of `ring` to `sink` after
+/// the previously installed hook has run.
+///
+/// Typically `ring` is a `static` and `sink` is `std::io::stderr()` or a file
+/// opened up front, so nothing needs to be set up while panicking.
+pub fn install_panic_dump<R, W>(ring: &'static R, sink: W)
+where
+    R: PanicDump + ?Sized,
+    W: Write + Send + 'static,
+{
+    let sink = Mutex::new(sink);
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        let mut sink = sink.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = writeln!(sink, "--- ring dump (oldest first) ---");
+        let _ = ring.dump_to(&mut *sink);
+        let _ = sink.flush();
+    }));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dump_fixed() {
+        let ring = Mutex::new(Fixed::from([0u8; 3]));
+        ring.lock().unwrap().extend([1, 2, 3, 4]);
+        let mut out = Vec::new();
+        ring.dump_to(&mut out).unwrap();
+        assert_eq!(out, b"2\n3\n4\n");
+
+        let _guard = ring.lock().unwrap();
+        let mut out = Vec::new();
+        ring.dump_to(&mut out).unwrap();
+        assert_eq!(out, b"<ring locked>\n");
+    }
+
+    #[test]
+    fn test_dump_recorder() {
+        let recorder = Recorder::new(4);
+        recorder.record("boot".into());
+        recorder.record("crash".into());
+        let mut out = Vec::new();
+        recorder.dump_to(&mut out).unwrap();
+        assert_eq!(out, b"boot\ncrash\n");
+    }
+}
@@ -0,0 +1,666 @@
+//! Crash-surviving ring stored in a memory-mapped file.
+//!
+//! The file starts with a header page holding two copies (A/B) of the ring
+//! state, each protected by a CRC-32 and stamped with a generation number.
+//! Updates always overwrite the older copy, so a crash in the middle of a
+//! header write leaves the other copy intact and reopening picks the newest
+//! valid one. Elements follow the header page.
+//!
+//! A push into a full ring first commits a state without the oldest element,
+//! then overwrites its slot, then commits the new element; a process crash at
+//! any point leaves a consistent (possibly one element shorter) ring.
+//!
+//! Nothing is flushed on the push path, so after a power loss or OS crash the
+//! kernel may have written the pages back in any order. Everything up to the
+//! last `flush` survives; elements pushed since may come back holding what
+//! their slots held before.
+//!
+//! Headers carry both the format version of this crate and a user version
+//! describing the element type; see `PersistentRing::open_versioned`.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::path::Path;
+use std::ptr;
+
+use memmap2::MmapMut;
+
+use super::checksum::crc32;
+use super::verify::{Report, Status};
+use super::Vec;
+
+pub const MAGIC: [u8; 8] = *b"RBUFPERS";
+/// Current on-disk format version. Version 1 lacked the user version field.
+pub const VERSION: u32 = 2;
+pub const HEADER_PAGE: usize = 4096;
+const SLOT_LEN: usize = 64;
+
+/// Element type that can be stored as raw bytes in a persistent ring.
+///
+/// # Safety
+///
+/// The type must have no padding, no pointers or references, and every bit
+/// pattern of its size must be a valid value.
+pub unsafe trait Plain: Copy + 'static {}
+
+macro_rules! impl_plain {
+    ($($t:ty),*) => {
+        $(unsafe impl Plain for $t {})*
+    };
+}
+
+impl_plain!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Plain, const N: usize> Plain for [T; N] {}
+
+/// Converts elements written with an older user format version.
+///
+/// Implemented for any `Fn(u32, &[u8]) -> io::Result<T>`.
+pub trait Migrate<T> {
+    /// Converts `old`, the raw bytes of one element written with user format
+    /// `version`, into the current element type.
+    fn migrate(&self, version: u32, old: &[u8]) -> io::Result<T>;
+}
+
+impl<T, F> Migrate<T> for F
+where
+    F: Fn(u32, &[u8]) -> io::Result<T>,
+{
+    #[inline]
+    fn migrate(&self, version: u32, old: &[u8]) -> io::Result<T> {
+        self(version, old)
+    }
+}
+
+/// Migration that refuses to convert anything.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoMigration;
+
+impl<T> Migrate<T> for NoMigration {
+    #[inline]
+    fn migrate(&self, _version: u32, _old: &[u8]) -> io::Result<T> {
+        Err(invalid("ring was written with an older user version"))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct State {
+    generation: u64,
+    head: u64,
+    len: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Header {
+    version: u32,
+    elem_size: usize,
+    capacity: usize,
+    user_version: u32,
+    state: State,
+}
+
+/// Fixed-capacity ring persisted in a memory-mapped file.
+#[derive(Debug)]
+pub struct PersistentRing<T> {
+    map: MmapMut,
+    file: File,
+    capacity: usize,
+    user_version: u32,
+    state: State,
+    _marker: PhantomData<T>,
+}
+
+impl<T> PersistentRing<T>
+where
+    T: Plain,
+{
+    /// Opens the ring stored at `path`, creating it with room for `capacity`
+    /// elements if the file does not exist or is empty.
+    ///
+    /// Fails with `InvalidData` if the file holds a ring of another element
+    /// size or capacity, or if neither header copy is valid.
+    #[inline]
+    pub fn open<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Self> {
+        Self::open_versioned(path, capacity, 0, &NoMigration)
+    }
+
+    /// Like `open`, tagging the elements with a user format `version`.
+    ///
+    /// A ring written with an older user version is converted element by
+    /// element with `migrate`, and may change element size and capacity on the
+    /// way (keeping the newest elements). Rings written by an older version of
+    /// this crate are upgraded transparently. Migration rewrites the file in
+    /// place and is not crash-safe; keep a copy if the data matters.
+    pub fn open_versioned<P, M>(
+        path: P,
+        capacity: usize,
+        version: u32,
+        migrate: &M,
+    ) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+        M: Migrate<T> + ?Sized,
+    {
+        assert!(capacity > 0 && mem::size_of::<T>() > 0);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let fresh = file.metadata()?.len() == 0;
+        if fresh {
+            file.set_len(Self::file_len(capacity))?;
+        }
+
+        // Safety: the mapping is only accessed through this value, and the
+        // file is expected not to be modified by other processes meanwhile.
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let mut ring = PersistentRing {
+            map,
+            file,
+            capacity,
+            user_version: version,
+            state: State {
+                generation: 0,
+                head: 0,
+                len: 0,
+            },
+            _marker: PhantomData,
+        };
+
+        if fresh {
+            ring.commit(0, 0);
+            ring.map.flush()?;
+            return Ok(ring);
+        }
+
+        let header = recover(&ring.map)?;
+        if header.user_version > version {
+            return Err(invalid("ring was written with a newer user version"));
+        }
+        if header.user_version < version {
+            ring.migrate_from(header, migrate)?;
+            return Ok(ring);
+        }
+        if header.elem_size != mem::size_of::<T>() || header.capacity != capacity {
+            return Err(invalid("ring was created with another layout"));
+        }
+        if (ring.map.len() as u64) < Self::file_len(capacity) {
+            return Err(invalid("file is smaller than the ring"));
+        }
+
+        ring.state = header.state;
+        if header.version < VERSION {
+            ring.commit(header.state.head, header.state.len);
+            ring.map.flush()?;
+        }
+        Ok(ring)
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// User format version the elements are tagged with.
+    #[inline]
+    pub fn user_version(&self) -> u32 {
+        self.user_version
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.state.len as usize
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.state.len == 0
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity
+    }
+
+    /// Appends `item`, evicting the oldest element if the ring is full.
+    pub fn push(&mut self, item: T) {
+        let mut head = self.state.head;
+        let mut len = self.state.len;
+        if len as usize == self.capacity {
+            head = (head + 1) % self.capacity as u64;
+            len -= 1;
+            self.commit(head, len);
+        }
+
+        let slot = (head + len) as usize % self.capacity;
+        self.write_slot(slot, item);
+        self.commit(head, len + 1);
+    }
+
+    /// Element at logical `index`, counting from the oldest one.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        Some(self.read_slot((self.state.head as usize + index) % self.capacity))
+    }
+
+    /// Elements from the oldest to the newest.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.commit(0, 0);
+    }
+
+    /// Checks both header copies and reports where the elements live. Elements
+    /// carry no checksum and are reported as unchecked.
+    #[inline]
+    pub fn verify(&self) -> Report {
+        verify_bytes(&self.map)
+    }
+
+    /// Flushes outstanding changes to disk.
+    #[inline]
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+
+    #[inline]
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    #[inline]
+    fn file_len(capacity: usize) -> u64 {
+        (HEADER_PAGE + capacity * mem::size_of::<T>()) as u64
+    }
+
+    fn read_slot(&self, slot: usize) -> T {
+        let offset = HEADER_PAGE + slot * mem::size_of::<T>();
+        let bytes = &self.map[offset..offset + mem::size_of::<T>()];
+        // Safety: `T: Plain` accepts any bit pattern and the range is in bounds.
+        unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) }
+    }
+
+    fn write_slot(&mut self, slot: usize, item: T) {
+        let offset = HEADER_PAGE + slot * mem::size_of::<T>();
+        let bytes = &mut self.map[offset..offset + mem::size_of::<T>()];
+        // Safety: the range is in bounds and `T: Plain` has no padding.
+        unsafe { ptr::write_unaligned(bytes.as_mut_ptr() as *mut T, item) }
+    }
+
+    fn migrate_from<M>(&mut self, header: Header, migrate: &M) -> io::Result<()>
+    where
+        M: Migrate<T> + ?Sized,
+    {
+        let needed = HEADER_PAGE + header.capacity * header.elem_size;
+        if header.capacity == 0 || self.map.len() < needed {
+            return Err(invalid("file is smaller than the ring"));
+        }
+
+        let State { head, len, .. } = header.state;
+        let skip = (len as usize).saturating_sub(self.capacity);
+        let mut items = Vec::with_capacity(len as usize - skip);
+        for index in skip..len as usize {
+            let slot = (head as usize + index) % header.capacity;
+            let offset = HEADER_PAGE + slot * header.elem_size;
+            let old = &self.map[offset..offset + header.elem_size];
+            items.push(migrate.migrate(header.user_version, old)?);
+        }
+
+        self.file.set_len(Self::file_len(self.capacity))?;
+        // Safety: see `open_versioned`.
+        self.map = unsafe { MmapMut::map_mut(&self.file)? };
+        self.state = header.state;
+        for (slot, item) in items.iter().enumerate() {
+            self.write_slot(slot, *item);
+        }
+        self.commit(0, items.len() as u64);
+        self.map.flush()
+    }
+
+    /// Panics if the committed state does not fit the ring: the head must be a
+    /// valid slot and the length at most the capacity.
+    #[inline]
+    pub(crate) fn check_invariants(&self) {
+        let capacity = self.capacity as u64;
+        assert!(self.state.head < capacity, "ring head out of bounds");
+        assert!(self.state.len <= capacity, "ring length exceeds capacity");
+        assert!(self.map.len() as u64 >= Self::file_len(self.capacity));
+    }
+
+    fn commit(&mut self, head: u64, len: u64) {
+        let generation = self.state.generation + 1;
+        let header = Header {
+            version: VERSION,
+            elem_size: mem::size_of::<T>(),
+            capacity: self.capacity,
+            user_version: self.user_version,
+            state: State {
+                generation,
+                head,
+                len,
+            },
+        };
+        let slot = (generation % 2) as usize * SLOT_LEN;
+        self.map[slot..slot + SLOT_LEN].copy_from_slice(&encode_header(&header));
+        self.state = header.state;
+        #[cfg(feature = "strict-checks")]
+        self.check_invariants();
+    }
+}
+
+fn encode_header(header: &Header) -> [u8; SLOT_LEN] {
+    let mut bytes = [0; SLOT_LEN];
+    bytes[0..8].copy_from_slice(&MAGIC);
+    bytes[8..12].copy_from_slice(&header.version.to_le_bytes());
+    bytes[12..16].copy_from_slice(&(header.elem_size as u32).to_le_bytes());
+    bytes[16..24].copy_from_slice(&(header.capacity as u64).to_le_bytes());
+    bytes[24..32].copy_from_slice(&header.state.generation.to_le_bytes());
+    bytes[32..40].copy_from_slice(&header.state.head.to_le_bytes());
+    bytes[40..48].copy_from_slice(&header.state.len.to_le_bytes());
+    bytes[48..52].copy_from_slice(&header.user_version.to_le_bytes());
+    let crc = crc32(&bytes[..52]);
+    bytes[52..56].copy_from_slice(&crc.to_le_bytes());
+    bytes
+}
+
+/// Decodes one header copy. Returns `None` for a damaged copy.
+///
+/// A copy of a newer, unknown format version is returned as long as its CRC
+/// validates under one of the known layouts, so a damaged version field is
+/// not mistaken for a newer version. Only its version and generation are
+/// filled in.
+fn decode_header(bytes: &[u8]) -> Option<Header> {
+    let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+    let crc_valid = |crc_at: usize| crc32(&bytes[..crc_at]) == u32_at(crc_at);
+    if bytes[0..8] != MAGIC {
+        return None;
+    }
+
+    let version = u32_at(8);
+    let (crc_at, user_version) = match version {
+        1 => (48, 0),
+        2 => (52, u32_at(48)),
+        _ if version > VERSION && (crc_valid(48) || crc_valid(52)) => {
+            return Some(Header {
+                version,
+                elem_size: 0,
+                capacity: 0,
+                user_version: 0,
+                state: State {
+                    generation: u64_at(24),
+                    head: 0,
+                    len: 0,
+                },
+            });
+        }
+        _ => return None,
+    };
+    if !crc_valid(crc_at) {
+        return None;
+    }
+
+    let header = Header {
+        version,
+        elem_size: u32_at(12) as usize,
+        capacity: u64_at(16) as usize,
+        user_version,
+        state: State {
+            generation: u64_at(24),
+            head: u64_at(32),
+            len: u64_at(40),
+        },
+    };
+    let capacity = header.capacity as u64;
+    if header.state.head >= capacity || header.state.len > capacity || header.elem_size == 0 {
+        return None;
+    }
+    Some(header)
+}
+
+/// Picks the newest intact header copy. Fails if that copy was written by a
+/// newer format version.
+fn recover(map: &[u8]) -> io::Result<Header> {
+    if map.len() < HEADER_PAGE {
+        return Err(invalid("file is smaller than the ring"));
+    }
+    let mut newest: Option<Header> = None;
+    for slot in 0..2 {
+        let header = match decode_header(&map[slot * SLOT_LEN..(slot + 1) * SLOT_LEN]) {
+            Some(header) => header,
+            None => continue,
+        };
+        if newest.is_none_or(|newest| header.state.generation > newest.state.generation) {
+            newest = Some(header);
+        }
+    }
+    let newest = newest.ok_or_else(|| invalid("no valid ring header"))?;
+    if newest.version > VERSION {
+        return Err(invalid("unsupported ring format version"));
+    }
+    Ok(newest)
+}
+
+/// Checks the ring file at `path` without opening it as a ring, so files of any
+/// element type and damaged files can be inspected.
+pub fn verify_file<P: AsRef<Path>>(path: P) -> io::Result<Report> {
+    Ok(verify_bytes(&std::fs::read(path)?))
+}
+
+fn verify_bytes(bytes: &[u8]) -> Report {
+    let mut report = Report::default();
+    for slot in 0..2 {
+        let range = slot * SLOT_LEN..(slot + 1) * SLOT_LEN;
+        let copy = match bytes.get(range.clone()) {
+            Some(copy) => copy,
+            None => {
+                report.push(
+                    range.start..bytes.len().max(range.start),
+                    Status::Corrupt,
+                    None,
+                );
+                break;
+            }
+        };
+        // A copy that was never written is not damage.
+        if copy.iter().all(|&byte| byte == 0) {
+            continue;
+        }
+        let status = match decode_header(copy) {
+            Some(header) if header.version <= VERSION => Status::Intact,
+            _ => Status::Corrupt,
+        };
+        report.push(range, status, None);
+    }
+
+    let header = match recover(bytes) {
+        Ok(header) => header,
+        Err(_) => return report,
+    };
+    for index in 0..header.state.len {
+        let slot = (header.state.head + index) as usize % header.capacity;
+        let start = HEADER_PAGE + slot * header.elem_size;
+        let range = start..start + header.elem_size;
+        let status = if range.end <= bytes.len() {
+            Status::Unchecked
+        } else {
+            Status::Corrupt
+        };
+        report.push(range, status, Some(index));
+    }
+    report.sort();
+    report
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_path() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!("rb-persistent-{}-{}", std::process::id(), n))
+    }
+
+    #[test]
+    fn test_reopen() {
+        let path = temp_path();
+        {
+            let mut ring = PersistentRing::<u32>::open(&path, 3).unwrap();
+            assert!(ring.is_empty());
+            ring.push(1);
+            ring.push(2);
+            ring.push(3);
+            ring.push(4);
+            ring.flush().unwrap();
+        }
+        let ring = PersistentRing::<u32>::open(&path, 3).unwrap();
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [2, 3, 4]);
+        assert!(PersistentRing::<u64>::open(&path, 3).is_err());
+        assert!(PersistentRing::<u32>::open(&path, 2).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_verify() {
+        let path = temp_path();
+        let mut ring = PersistentRing::<u32>::open(&path, 2).unwrap();
+        assert_eq!(ring.verify().regions.len(), 1);
+        for value in 0..3 {
+            ring.push(value);
+        }
+        ring.check_invariants();
+        let report = ring.verify();
+        assert!(report.is_clean());
+        let elements: Vec<_> = report
+            .regions
+            .iter()
+            .filter_map(|region| Some((region.seq?, region.range.start)))
+            .collect();
+        assert_eq!(elements, [(1, HEADER_PAGE), (0, HEADER_PAGE + 4)]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_upgrade_format_v1() {
+        let path = temp_path();
+        {
+            let mut ring = PersistentRing::<u16>::open(&path, 4).unwrap();
+            ring.push(7);
+            ring.push(8);
+            // Rewrite both header copies in the version 1 layout.
+            for (slot, generation) in [(0, 2u64), (1, 1)] {
+                let bytes = &mut ring.map[slot * SLOT_LEN..(slot + 1) * SLOT_LEN];
+                bytes.fill(0);
+                bytes[0..8].copy_from_slice(&MAGIC);
+                bytes[8..12].copy_from_slice(&1u32.to_le_bytes());
+                bytes[12..16].copy_from_slice(&2u32.to_le_bytes());
+                bytes[16..24].copy_from_slice(&4u64.to_le_bytes());
+                bytes[24..32].copy_from_slice(&generation.to_le_bytes());
+                bytes[40..48].copy_from_slice(&generation.to_le_bytes());
+                let crc = crc32(&bytes[..48]);
+                bytes[48..52].copy_from_slice(&crc.to_le_bytes());
+            }
+        }
+        let mut ring = PersistentRing::<u16>::open(&path, 4).unwrap();
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [7, 8]);
+        ring.push(9);
+        let header = recover(&ring.map).unwrap();
+        assert_eq!(header.version, VERSION);
+        assert_eq!(header.state.len, 3);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_user_migration() {
+        let path = temp_path();
+        {
+            let mut ring = PersistentRing::<u16>::open(&path, 3).unwrap();
+            for value in 1..=5 {
+                ring.push(value);
+            }
+        }
+        assert!(PersistentRing::<u32>::open_versioned(&path, 3, 1, &NoMigration).is_err());
+
+        let widen = |version: u32, old: &[u8]| {
+            assert_eq!(version, 0);
+            Ok(u16::from_le_bytes([old[0], old[1]]) as u32 * 1000)
+        };
+        {
+            let ring = PersistentRing::<u32>::open_versioned(&path, 2, 1, &widen).unwrap();
+            assert_eq!(ring.iter().collect::<Vec<_>>(), [4000, 5000]);
+        }
+        let ring = PersistentRing::<u32>::open_versioned(&path, 2, 1, &NoMigration).unwrap();
+        assert_eq!(ring.user_version(), 1);
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [4000, 5000]);
+        assert!(PersistentRing::<u32>::open(&path, 2).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_header() {
+        let path = temp_path();
+        {
+            let mut ring = PersistentRing::<[u8; 2]>::open(&path, 4).unwrap();
+            ring.push([1, 1]);
+            ring.push([2, 2]);
+            // Damage the copy written last, as a crash mid-write would.
+            let slot = (ring.state.generation % 2) as usize * SLOT_LEN;
+            ring.map[slot + 40] ^= 0xff;
+        }
+        let report = verify_file(&path).unwrap();
+        assert_eq!(report.corrupt().count(), 1);
+        let elements: Vec<_> = report
+            .recoverable()
+            .filter_map(|region| region.seq)
+            .collect();
+        assert_eq!(elements, [0]);
+
+        let ring = PersistentRing::<[u8; 2]>::open(&path, 4).unwrap();
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [[1, 1]]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_damaged_version_field() {
+        let path = temp_path();
+        {
+            let mut ring = PersistentRing::<u32>::open(&path, 4).unwrap();
+            ring.push(1);
+            ring.push(2);
+            // A flipped bit in the newest copy looks like a newer version.
+            let slot = (ring.state.generation % 2) as usize * SLOT_LEN;
+            ring.map[slot + 8] ^= 0x80;
+        }
+        let ring = PersistentRing::<u32>::open(&path, 4).unwrap();
+        assert_eq!(ring.iter().collect::<Vec<_>>(), [1]);
+        drop(ring);
+
+        // An intact copy of a newer version is still refused.
+        let mut bytes = std::fs::read(&path).unwrap();
+        let mut header = decode_header(&bytes[..SLOT_LEN]).unwrap();
+        header.version = VERSION + 1;
+        header.state.generation += 2;
+        bytes[..SLOT_LEN].copy_from_slice(&encode_header(&header));
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(PersistentRing::<u32>::open(&path, 4).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,11 @@
+//! Common types and traits, for glob import.
+
+pub use crate::builder::FixedBuilder;
+pub use crate::clock::Clock;
+pub use crate::copy_ext::CopyRingExt;
+pub use crate::counted::Counted;
+pub use crate::evict::{EvictHook, Evicting};
+pub use crate::fixed::Fixed;
+pub use crate::ringbuf;
+pub use crate::sampling::RandomSource;
+pub use crate::traits::{FromElements, Slice, SliceMut};
@@ -0,0 +1,190 @@
+use super::fixed::Fixed;
+use super::sampling::RandomSource;
+use super::traits::SliceMut;
+use super::Vec;
+
+/// Ring whose elements carry a sampling weight.
+///
+/// Weights live in a sum-tree indexed by physical slot next to the ring, so
+/// pushing, updating a weight and drawing a weighted sample are all
+/// `O(log n)`. This is the usual structure behind prioritized experience
+/// replay.
+#[derive(Clone, Debug)]
+pub struct PrioritizedRing<S> {
+    items: Fixed<S>,
+    tree: Vec<f64>,
+    leaves: usize,
+}
+
+impl<S> PrioritizedRing<S>
+where
+    S: SliceMut,
+{
+    /// Creates a ring over `storage`, giving every existing element `weight`.
+    pub fn new(storage: S, weight: f64) -> Self {
+        assert_weight(weight);
+        let items = Fixed::from(storage);
+        let leaves = items.len().next_power_of_two();
+        let mut tree = alloc::vec![0.0; 2 * leaves];
+        tree[leaves..leaves + items.len()].fill(weight);
+        for node in (1..leaves).rev() {
+            tree[node] = tree[2 * node] + tree[2 * node + 1];
+        }
+        PrioritizedRing {
+            items,
+            tree,
+            leaves,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    #[inline]
+    pub fn items(&self) -> &Fixed<S> {
+        &self.items
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> &S::Element {
+        self.items.get(index)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut S::Element {
+        self.items.get_mut(index)
+    }
+
+    /// Pushes `item` with `weight`, returning the evicted element.
+    pub fn push(&mut self, item: S::Element, weight: f64) -> S::Element {
+        assert_weight(weight);
+        let slot = self.items.first();
+        let old = self.items.push(item);
+        self.update(slot, weight);
+        old
+    }
+
+    #[inline]
+    pub fn weight(&self, index: usize) -> f64 {
+        self.tree[self.leaves + self.physical(index)]
+    }
+
+    #[inline]
+    pub fn set_weight(&mut self, index: usize, weight: f64) {
+        assert_weight(weight);
+        self.update(self.physical(index), weight);
+    }
+
+    #[inline]
+    pub fn total_weight(&self) -> f64 {
+        self.tree[1]
+    }
+
+    /// Draws a logical index with probability proportional to its weight.
+    ///
+    /// Returns `None` if all weights are zero.
+    pub fn sample<R>(&self, rng: &mut R) -> Option<(usize, &S::Element)>
+    where
+        R: RandomSource + ?Sized,
+    {
+        if self.total_weight() <= 0.0 {
+            return None;
+        }
+
+        let unit = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        let mut target = unit * self.total_weight();
+        let mut node = 1;
+        while node < self.leaves {
+            let left = 2 * node;
+            // Rounding can leave `target` just past the left sum even when the
+            // right subtree is empty; never descend into a zero-weight subtree.
+            if target < self.tree[left] || self.tree[left + 1] <= 0.0 {
+                node = left;
+            } else {
+                target -= self.tree[left];
+                node = left + 1;
+            }
+        }
+
+        let len = self.items.len();
+        let index = (node - self.leaves + len - self.items.first()) % len;
+        Some((index, self.items.get(index)))
+    }
+
+    #[inline]
+    fn physical(&self, index: usize) -> usize {
+        (self.items.first() + index) % self.items.len()
+    }
+
+    fn update(&mut self, slot: usize, weight: f64) {
+        let mut node = self.leaves + slot;
+        self.tree[node] = weight;
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = self.tree[2 * node] + self.tree[2 * node + 1];
+        }
+    }
+}
+
+#[inline]
+fn assert_weight(weight: f64) {
+    assert!(weight >= 0.0 && weight.is_finite(), "invalid weight");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rng() -> impl FnMut() -> u64 {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }
+    }
+
+    #[test]
+    fn test_weights_follow_push() {
+        let mut ring = PrioritizedRing::new([0u8; 3], 1.0);
+        assert_eq!(ring.total_weight(), 3.0);
+        ring.push(1, 4.0);
+        ring.push(2, 0.5);
+        assert_eq!(ring.weight(0), 1.0);
+        assert_eq!(ring.weight(1), 4.0);
+        assert_eq!(ring.weight(2), 0.5);
+        ring.set_weight(0, 0.0);
+        assert_eq!(ring.total_weight(), 4.5);
+    }
+
+    #[test]
+    fn test_sample_only_weighted() {
+        let mut ring = PrioritizedRing::new(vec![0; 5], 0.0);
+        assert!(ring.sample(&mut rng()).is_none());
+        ring.push(7, 2.0);
+        let mut rng = rng();
+        for _ in 0..50 {
+            assert_eq!(ring.sample(&mut rng), Some((4, &7)));
+        }
+    }
+
+    #[test]
+    fn test_sample_proportional() {
+        let mut ring = PrioritizedRing::new([0; 2], 0.0);
+        ring.push(1, 1.0);
+        ring.push(2, 3.0);
+        let mut rng = rng();
+        let hits = (0..4000)
+            .filter(|_| *ring.sample(&mut rng).unwrap().1 == 2)
+            .count();
+        assert!((2800..3200).contains(&hits));
+    }
+}
@@ -0,0 +1,129 @@
+//! Python bindings for rolling windows of numeric samples.
+//!
+//! Build the crate as a `cdylib` with the `python` feature (and pyo3's
+//! `extension-module` feature) to get an importable `rust_ringbuf` module.
+
+use numpy::PyArray1;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+
+use super::fixed::Fixed;
+use super::{Box, Vec};
+
+/// Fixed window of the latest `float` samples, exported to Python as
+/// `FixedRing`.
+#[pyclass(name = "FixedRing")]
+#[derive(Debug)]
+pub struct PyFixed {
+    inner: Fixed<Box<[f64]>>,
+}
+
+#[pymethods]
+impl PyFixed {
+    #[new]
+    #[pyo3(signature = (capacity, fill = 0.0))]
+    pub fn new(capacity: usize, fill: f64) -> PyResult<Self> {
+        if capacity == 0 {
+            return Err(PyValueError::new_err("capacity must be positive"));
+        }
+        Ok(PyFixed {
+            inner: Fixed::from(alloc::vec![fill; capacity].into_boxed_slice()),
+        })
+    }
+
+    /// Appends `value` and returns the evicted oldest sample.
+    pub fn push(&mut self, value: f64) -> f64 {
+        self.inner.push(value)
+    }
+
+    /// Appends every sample of `values`, oldest first.
+    pub fn extend(&mut self, values: Vec<f64>) {
+        self.inner.extend_from_slice(&values);
+    }
+
+    /// Copies the samples, oldest first, into a new numpy array.
+    pub fn to_numpy<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_vec(py, self.values())
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Sample at `index`, counting from the oldest one. Negative indices count
+    /// from the newest one.
+    pub fn __getitem__(&self, index: isize) -> PyResult<f64> {
+        let len = self.inner.len() as isize;
+        let index = if index < 0 { index + len } else { index };
+        if !(0..len).contains(&index) {
+            return Err(PyIndexError::new_err("ring index out of range"));
+        }
+        Ok(*self.inner.get(index as usize))
+    }
+
+    pub fn __iter__(&self) -> PyFixedIter {
+        PyFixedIter {
+            values: self.values().into_iter(),
+        }
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("FixedRing({:?})", self.values())
+    }
+}
+
+impl PyFixed {
+    fn values(&self) -> Vec<f64> {
+        self.inner.iter().copied().collect()
+    }
+}
+
+/// Iterator over a snapshot of a `FixedRing`, oldest sample first.
+#[pyclass(name = "FixedRingIter")]
+#[derive(Debug)]
+pub struct PyFixedIter {
+    values: alloc::vec::IntoIter<f64>,
+}
+
+#[pymethods]
+impl PyFixedIter {
+    pub fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    pub fn __next__(&mut self) -> Option<f64> {
+        self.values.next()
+    }
+}
+
+/// Python module exposing the ring classes.
+#[pymodule]
+#[pyo3(name = "rust_ringbuf")]
+pub fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyFixed>()?;
+    module.add_class::<PyFixedIter>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fixed_ring() {
+        let mut rb = PyFixed::new(3, 0.0).unwrap();
+        assert_eq!(rb.push(1.0), 0.0);
+        rb.extend(vec![2.0, 3.0, 4.0]);
+        assert_eq!(rb.__len__(), 3);
+        assert_eq!(rb.__getitem__(0).unwrap(), 2.0);
+        assert_eq!(rb.__getitem__(-1).unwrap(), 4.0);
+        assert_eq!(rb.__iter__().__next__(), Some(2.0));
+        assert_eq!(rb.__repr__(), "FixedRing([2.0, 3.0, 4.0])");
+
+        Python::initialize();
+        Python::attach(|_| {
+            assert!(rb.__getitem__(3).is_err());
+            assert!(PyFixed::new(0, 0.0).is_err());
+        });
+    }
+}
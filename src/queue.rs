@@ -0,0 +1,435 @@
+//! Bounded FIFO queue whose behaviour when full is a type parameter.
+
+use core::fmt;
+
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// Decides what `Queue::push` does, and returns, when the queue is full.
+///
+/// Policies can keep state; a queue owns its policy and hands it out with
+/// `policy` and `policy_mut`.
+pub trait OverflowPolicy<T>: Sized {
+    /// What `Queue::push` returns.
+    type Outcome;
+
+    fn push<S>(queue: &mut Queue<S, Self>, item: T) -> Self::Outcome
+    where
+        S: SliceMut<Element = Option<T>>;
+}
+
+/// Evicts the oldest element to make room, like `Fixed::push`. `push`
+/// returns the evicted element.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Overwrite;
+
+/// Refuses the new element. `push` fails with it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Reject;
+
+/// Silently drops the new element. `push` returns whether it was stored.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DropNewest;
+
+impl<T> OverflowPolicy<T> for Overwrite {
+    type Outcome = Option<T>;
+
+    #[inline]
+    fn push<S>(queue: &mut Queue<S, Self>, item: T) -> Option<T>
+    where
+        S: SliceMut<Element = Option<T>>,
+    {
+        let evicted = if queue.is_full() { queue.pop() } else { None };
+        queue.slots.push(Some(item));
+        queue.len += 1;
+        evicted
+    }
+}
+
+impl<T> OverflowPolicy<T> for Reject {
+    type Outcome = Result<(), QueueFull<T>>;
+
+    #[inline]
+    fn push<S>(queue: &mut Queue<S, Self>, item: T) -> Result<(), QueueFull<T>>
+    where
+        S: SliceMut<Element = Option<T>>,
+    {
+        queue.try_push(item)
+    }
+}
+
+impl<T> OverflowPolicy<T> for DropNewest {
+    type Outcome = bool;
+
+    #[inline]
+    fn push<S>(queue: &mut Queue<S, Self>, item: T) -> bool
+    where
+        S: SliceMut<Element = Option<T>>,
+    {
+        queue.try_push(item).is_ok()
+    }
+}
+
+/// Chooses which element a full queue gives up for a new one.
+pub trait EvictionPolicy<T> {
+    /// Index, counted from the oldest element, of the one to evict to make
+    /// room for `incoming`, or `None` to drop `incoming` instead. An index
+    /// past the end also drops `incoming`.
+    fn victim<'a>(&mut self, elements: impl Iterator<Item = &'a T>, incoming: &T) -> Option<usize>
+    where
+        T: 'a;
+}
+
+/// Overflow policy evicting whichever element an `EvictionPolicy` chooses.
+/// `push` returns the evicted element, or the new one if it was dropped.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Evict<E>(pub E);
+
+impl<T, E> OverflowPolicy<T> for Evict<E>
+where
+    E: EvictionPolicy<T>,
+{
+    type Outcome = Option<T>;
+
+    fn push<S>(queue: &mut Queue<S, Self>, item: T) -> Option<T>
+    where
+        S: SliceMut<Element = Option<T>>,
+    {
+        let item = match queue.try_push(item) {
+            Ok(()) => return None,
+            Err(QueueFull(item)) => item,
+        };
+        let elements = queue
+            .slots
+            .range(queue.oldest_slot()..)
+            .into_iter()
+            .map(|slot| slot.as_ref().unwrap());
+        let evicted = queue
+            .policy
+            .0
+            .victim(elements, &item)
+            .and_then(|index| queue.remove(index));
+        match evicted {
+            Some(evicted) => {
+                queue.slots.push(Some(item));
+                queue.len += 1;
+                Some(evicted)
+            }
+            None => Some(item),
+        }
+    }
+}
+
+/// Evicts the oldest element; the same as `Overwrite`, as an
+/// `EvictionPolicy`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EvictOldest;
+
+impl<T> EvictionPolicy<T> for EvictOldest {
+    #[inline]
+    fn victim<'a>(&mut self, _: impl Iterator<Item = &'a T>, _: &T) -> Option<usize>
+    where
+        T: 'a,
+    {
+        Some(0)
+    }
+}
+
+/// Evicts the element with the lowest priority, as computed by the closure,
+/// the oldest one among equals. The new element is dropped instead if its
+/// priority is lower still.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EvictLowest<F>(pub F);
+
+impl<T, F, K> EvictionPolicy<T> for EvictLowest<F>
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    fn victim<'a>(&mut self, elements: impl Iterator<Item = &'a T>, incoming: &T) -> Option<usize>
+    where
+        T: 'a,
+    {
+        let (index, lowest) = elements
+            .map(&mut self.0)
+            .enumerate()
+            .reduce(|lowest, next| if next.1 < lowest.1 { next } else { lowest })?;
+        (lowest <= (self.0)(incoming)).then_some(index)
+    }
+}
+
+/// Evicts the oldest element matching the predicate. The new element is
+/// dropped instead if none does.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EvictWhere<F>(pub F);
+
+impl<T, F> EvictionPolicy<T> for EvictWhere<F>
+where
+    F: FnMut(&T) -> bool,
+{
+    #[inline]
+    fn victim<'a>(&mut self, mut elements: impl Iterator<Item = &'a T>, _: &T) -> Option<usize>
+    where
+        T: 'a,
+    {
+        elements.position(&mut self.0)
+    }
+}
+
+/// A full queue refused an element, which is given back.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QueueFull<T>(pub T);
+
+impl<T> fmt::Display for QueueFull<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("queue is full")
+    }
+}
+
+/// Bounded FIFO queue over a ring of optional slots.
+///
+/// The elements occupy the newest `len` logical slots of the ring and the
+/// rest are `None`, so pushing into a queue that is not full only evicts an
+/// empty slot. What happens when it is full is up to the policy `P`.
+#[derive(Clone, Debug)]
+pub struct Queue<S, P> {
+    slots: Fixed<S>,
+    len: usize,
+    policy: P,
+}
+
+impl<S, T, P> Queue<S, P>
+where
+    S: SliceMut<Element = Option<T>>,
+    P: OverflowPolicy<T>,
+{
+    /// Creates an empty queue over `storage`, which is cleared.
+    #[inline]
+    pub fn new(storage: S) -> Self
+    where
+        P: Default,
+    {
+        Self::with_policy(storage, P::default())
+    }
+
+    /// Like `new`, with an explicit, possibly stateful, policy.
+    pub fn with_policy(storage: S, policy: P) -> Self {
+        let mut slots = Fixed::from(storage);
+        slots.iter_mut().for_each(|slot| *slot = None);
+        Queue {
+            slots,
+            len: 0,
+            policy,
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    #[inline]
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    #[inline]
+    pub fn policy_mut(&mut self) -> &mut P {
+        &mut self.policy
+    }
+
+    /// Appends `item`, deferring to the policy if the queue is full.
+    #[inline]
+    pub fn push(&mut self, item: T) -> P::Outcome {
+        P::push(self, item)
+    }
+
+    /// Appends `item` unless the queue is full, whatever the policy.
+    #[inline]
+    pub fn try_push(&mut self, item: T) -> Result<(), QueueFull<T>> {
+        if self.is_full() {
+            return Err(QueueFull(item));
+        }
+        self.slots.push(Some(item));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the oldest element.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        let oldest = self.oldest_slot();
+        let item = self.slots[oldest].take()?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    /// Removes the element `index` positions after the oldest one, moving
+    /// the older elements up to close the gap.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let oldest = self.oldest_slot();
+        let item = self.slots[oldest + index].take();
+        for slot in (oldest..oldest + index).rev() {
+            let moved = self.slots[slot].take();
+            self.slots[slot + 1] = moved;
+        }
+        self.len -= 1;
+        item
+    }
+
+    /// The oldest element.
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// The newest element.
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Element `index` positions after the oldest one.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.slots[self.oldest_slot() + index].as_ref()
+    }
+
+    /// Elements, oldest first.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> impl DoubleEndedIterator<Item = &'a T> + 'a
+    where
+        T: 'a,
+    {
+        self.slots
+            .range(self.oldest_slot()..)
+            .into_iter()
+            .map(|slot| slot.as_ref().unwrap())
+    }
+
+    /// Drops every element.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+
+    /// Logical slot of the oldest element.
+    #[inline]
+    fn oldest_slot(&self) -> usize {
+        self.capacity() - self.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fifo() {
+        let mut queue: Queue<_, Reject> = Queue::new([None; 3]);
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+        queue.push(1).unwrap();
+        queue.push(2).unwrap();
+        assert_eq!(queue.front(), Some(&1));
+        assert_eq!(queue.back(), Some(&2));
+        assert_eq!(queue.pop(), Some(1));
+        queue.push(3).unwrap();
+        queue.push(4).unwrap();
+        assert!(queue.is_full());
+        assert_eq!(queue.push(5), Err(QueueFull(5)));
+        assert!(queue.iter().eq(&[2, 3, 4]));
+        assert!(queue.iter().rev().eq(&[4, 3, 2]));
+        assert_eq!(queue.get(3), None);
+
+        queue.clear();
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.back(), None);
+    }
+
+    #[test]
+    fn test_policies() {
+        let mut overwrite: Queue<_, Overwrite> = Queue::new(vec![None; 2]);
+        assert_eq!(overwrite.push('a'), None);
+        assert_eq!(overwrite.push('b'), None);
+        assert_eq!(overwrite.push('c'), Some('a'));
+        assert!(overwrite.iter().eq(&['b', 'c']));
+
+        let mut drop_newest: Queue<_, DropNewest> = Queue::new(vec![None; 2]);
+        assert!(drop_newest.push('a'));
+        assert!(drop_newest.push('b'));
+        assert!(!drop_newest.push('c'));
+        assert!(drop_newest.iter().eq(&['a', 'b']));
+        assert_eq!(drop_newest.try_push('d'), Err(QueueFull('d')));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut queue: Queue<_, Reject> = Queue::new([None; 4]);
+        (1..=4).for_each(|i| queue.push(i).unwrap());
+        assert_eq!(queue.remove(1), Some(2));
+        assert_eq!(queue.remove(3), None);
+        assert!(queue.iter().eq(&[1, 3, 4]));
+        queue.push(5).unwrap();
+        assert!(queue.iter().eq(&[1, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_evict_lowest() {
+        #[derive(Debug, PartialEq)]
+        struct Job(&'static str, u8);
+
+        let priority: fn(&Job) -> u8 = |job| job.1;
+        let mut queue = Queue::with_policy(vec![None, None, None], Evict(EvictLowest(priority)));
+        assert_eq!(queue.push(Job("a", 2)), None);
+        assert_eq!(queue.push(Job("b", 1)), None);
+        assert_eq!(queue.push(Job("c", 1)), None);
+        assert_eq!(queue.push(Job("d", 3)), Some(Job("b", 1)));
+        assert_eq!(queue.push(Job("e", 0)), Some(Job("e", 0)));
+        assert!(queue.iter().map(|job| job.0).eq(["a", "c", "d"]));
+    }
+
+    #[test]
+    fn test_evict_where() {
+        let mut checked = 0;
+        let mut queue = Queue::with_policy(
+            [None; 3],
+            Evict(EvictWhere(|x: &i32| {
+                checked += 1;
+                x % 2 == 0
+            })),
+        );
+        (1..=3).for_each(|i| assert_eq!(queue.push(i), None));
+        assert_eq!(queue.push(4), Some(2));
+        assert_eq!(queue.push(5), Some(4));
+        assert_eq!(queue.push(7), Some(7));
+        assert!(queue.iter().eq(&[1, 3, 5]));
+        assert_eq!(checked, 8);
+
+        let mut oldest = Queue::with_policy([None; 2], Evict(EvictOldest));
+        oldest.push(1);
+        oldest.push(2);
+        assert_eq!(oldest.push(3), Some(1));
+    }
+}
@@ -0,0 +1,139 @@
+use super::clock::Clock;
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// Sliding-window rate limiter.
+///
+/// Allows at most `limit` events in any window of `window` clock ticks, where
+/// `limit` is the length of the backing storage. The timestamps of the last
+/// `limit` accepted events are kept in a `Fixed` ring, so the oldest of them is
+/// always the one that decides whether a new event fits into the window.
+#[derive(Clone, Debug)]
+pub struct RateLimiter<S, C> {
+    events: Fixed<S>,
+    recorded: usize,
+    window: u64,
+    clock: C,
+}
+
+impl<S, C> RateLimiter<S, C>
+where
+    S: SliceMut<Element = u64>,
+    C: Clock,
+{
+    /// Creates a limiter over `storage`. Panics if `storage` is empty.
+    #[inline]
+    pub fn new(storage: S, window: u64, clock: C) -> Self {
+        RateLimiter {
+            events: Fixed::from(storage),
+            recorded: 0,
+            window,
+            clock,
+        }
+    }
+
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.events.len()
+    }
+
+    #[inline]
+    pub fn window(&self) -> u64 {
+        self.window
+    }
+
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns whether an event would be accepted now, without recording it.
+    #[inline]
+    pub fn check(&self) -> bool {
+        self.check_at(self.clock.now())
+    }
+
+    /// Records an event if it fits into the current window.
+    pub fn try_acquire(&mut self) -> bool {
+        let now = self.clock.now();
+        if !self.check_at(now) {
+            return false;
+        }
+
+        self.events.push(now);
+        if self.recorded < self.limit() {
+            self.recorded += 1;
+        }
+        true
+    }
+
+    /// Number of events that would be accepted if acquired right now.
+    pub fn remaining(&self) -> usize {
+        let now = self.clock.now();
+        let in_window = self
+            .events
+            .iter()
+            .skip(self.limit() - self.recorded)
+            .filter(|&&at| now.saturating_sub(at) < self.window)
+            .count();
+        self.limit() - in_window
+    }
+
+    /// Forgets all recorded events.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.recorded = 0;
+    }
+
+    fn check_at(&self, now: u64) -> bool {
+        // While the ring is not yet populated the limit cannot be reached.
+        // Otherwise the slot at logical index 0 holds the oldest of the last
+        // `limit` events and is the one the next push would replace.
+        self.recorded < self.limit() || now.saturating_sub(self.events[0]) >= self.window
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn test_limit_within_window() {
+        let now = Cell::new(0);
+        let mut limiter = RateLimiter::new([0u64; 3], 10, || now.get());
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.check());
+        assert!(!limiter.try_acquire());
+        assert_eq!(limiter.remaining(), 0);
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let now = Cell::new(0);
+        let mut limiter = RateLimiter::new([0u64; 2], 10, || now.get());
+        assert!(limiter.try_acquire());
+        now.set(5);
+        assert!(limiter.try_acquire());
+        now.set(9);
+        assert!(!limiter.try_acquire());
+        now.set(10);
+        assert_eq!(limiter.remaining(), 1);
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+        now.set(15);
+        assert!(limiter.try_acquire());
+    }
+
+    #[test]
+    fn test_reset() {
+        let now = Cell::new(100);
+        let mut limiter = RateLimiter::new(vec![0u64; 1], 10, || now.get());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+        limiter.reset();
+        assert!(limiter.try_acquire());
+    }
+}
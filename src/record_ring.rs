@@ -0,0 +1,253 @@
+use core::fmt;
+
+use super::fixed::Fixed;
+use super::ring_slice::RingSlice;
+use super::traits::{Slice, SliceMut};
+
+/// Bytes of the length prefix in front of every record.
+pub const PREFIX_LEN: usize = 4;
+
+/// Variable-length byte records in a byte ring.
+///
+/// Each record is stored as a little-endian `u32` length followed by its
+/// bytes. The records occupy the newest `bytes_used` bytes of the ring;
+/// pushing a record evicts whole oldest records until it fits.
+#[derive(Clone, Debug)]
+pub struct RecordRing<S> {
+    bytes: Fixed<S>,
+    used: usize,
+    count: usize,
+}
+
+/// A record does not fit in the ring even when it is empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RecordTooLarge {
+    pub len: usize,
+    pub max: usize,
+}
+
+impl fmt::Display for RecordTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "record of {} bytes exceeds the maximum of {}",
+            self.len, self.max
+        )
+    }
+}
+
+impl<S> RecordRing<S>
+where
+    S: Slice<Element = u8>,
+{
+    /// Creates an empty record ring over `storage`.
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        RecordRing {
+            bytes: Fixed::from(storage),
+            used: 0,
+            count: 0,
+        }
+    }
+
+    /// Longest record that fits in the ring along with its prefix.
+    #[inline]
+    pub fn max_record_len(&self) -> usize {
+        self.bytes
+            .len()
+            .saturating_sub(PREFIX_LEN)
+            .min(u32::MAX as usize)
+    }
+
+    /// Number of records held.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Number of bytes used by the records held, prefixes included.
+    #[inline]
+    pub fn bytes_used(&self) -> usize {
+        self.used
+    }
+
+    /// Records, oldest first.
+    #[inline]
+    pub fn iter(&self) -> Records<'_, S> {
+        Records {
+            bytes: &self.bytes,
+            pos: self.bytes.len() - self.used,
+            remaining: self.count,
+        }
+    }
+}
+
+impl<S> RecordRing<S>
+where
+    S: SliceMut<Element = u8>,
+{
+    /// Appends `record`, evicting whole oldest records until it fits.
+    /// Returns the number of records evicted.
+    #[inline]
+    pub fn push_record(&mut self, record: &[u8]) -> Result<usize, RecordTooLarge> {
+        self.push_record_parts(&[record])
+    }
+
+    /// Appends the concatenation of `parts` as one record, like
+    /// `push_record`.
+    pub fn push_record_parts(&mut self, parts: &[&[u8]]) -> Result<usize, RecordTooLarge> {
+        let len: usize = parts.iter().map(|part| part.len()).sum();
+        // Storage shorter than a prefix cannot even hold an empty record.
+        if PREFIX_LEN + len > self.bytes.len() || len > self.max_record_len() {
+            return Err(RecordTooLarge {
+                len,
+                max: self.max_record_len(),
+            });
+        }
+
+        let needed = PREFIX_LEN + len;
+        let mut evicted = 0;
+        while self.bytes.len() - self.used < needed {
+            let oldest = self.bytes.len() - self.used;
+            self.used -= PREFIX_LEN + read_len(&self.bytes, oldest);
+            self.count -= 1;
+            evicted += 1;
+        }
+
+        self.bytes.extend_from_slice(&(len as u32).to_le_bytes());
+        for part in parts {
+            self.bytes.extend_from_slice(part);
+        }
+        self.used += needed;
+        self.count += 1;
+        Ok(evicted)
+    }
+
+    /// Drops every record.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.used = 0;
+        self.count = 0;
+    }
+}
+
+fn read_len<S>(bytes: &Fixed<S>, pos: usize) -> usize
+where
+    S: Slice<Element = u8>,
+{
+    let mut prefix = [0; PREFIX_LEN];
+    bytes.copy_out(pos, &mut prefix);
+    u32::from_le_bytes(prefix) as usize
+}
+
+/// Iterator over the records of a `RecordRing`, oldest first.
+///
+/// A record may cross the physical end of the storage, so each one is a
+/// `RingSlice`.
+#[derive(Debug)]
+pub struct Records<'a, S> {
+    bytes: &'a Fixed<S>,
+    pos: usize,
+    remaining: usize,
+}
+
+impl<'a, S> Iterator for Records<'a, S>
+where
+    S: Slice<Element = u8>,
+{
+    type Item = RingSlice<'a, u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let start = self.pos + PREFIX_LEN;
+        let end = start + read_len(self.bytes, self.pos);
+        self.pos = end;
+        self.remaining -= 1;
+        Some(self.bytes.range(start..end))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<S> ExactSizeIterator for Records<'_, S> where S: Slice<Element = u8> {}
+
+impl<'a, S> IntoIterator for &'a RecordRing<S>
+where
+    S: Slice<Element = u8>,
+{
+    type Item = RingSlice<'a, u8>;
+    type IntoIter = Records<'a, S>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn records<S: Slice<Element = u8>>(ring: &RecordRing<S>) -> Vec<Vec<u8>> {
+        ring.iter()
+            .map(|record| record.iter().copied().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_push_and_evict() {
+        let mut ring = RecordRing::new([0u8; 16]);
+        assert_eq!(ring.max_record_len(), 12);
+        assert_eq!(ring.push_record(b"hello"), Ok(0));
+        assert_eq!(ring.push_record(b""), Ok(0));
+        assert_eq!(ring.bytes_used(), 13);
+        assert_eq!(records(&ring), [&b"hello"[..], b""]);
+
+        // Needs 7 bytes with 3 free: "hello" goes, the empty record stays.
+        assert_eq!(ring.push_record(b"abc"), Ok(1));
+        assert_eq!(records(&ring), [&b""[..], b"abc"]);
+        assert_eq!(ring.len(), 2);
+
+        // Wraps around the physical end of the storage.
+        assert_eq!(ring.push_record(b"01234"), Ok(1));
+        assert_eq!(records(&ring), [&b"abc"[..], b"01234"]);
+
+        assert_eq!(ring.push_record(&[7; 12]), Ok(2));
+        assert_eq!(records(&ring), [[7; 12]]);
+
+        assert_eq!(ring.push_record_parts(&[b"ab", b"", b"cd"]), Ok(1));
+        assert_eq!(records(&ring), [b"abcd"]);
+    }
+
+    #[test]
+    fn test_too_large() {
+        let mut ring = RecordRing::new(vec![0u8; 8]);
+        ring.push_record(b"x").unwrap();
+        assert_eq!(
+            ring.push_record(b"12345"),
+            Err(RecordTooLarge { len: 5, max: 4 })
+        );
+        assert_eq!(records(&ring), [b"x"]);
+
+        ring.clear();
+        assert!(ring.is_empty());
+        assert_eq!(ring.iter().len(), 0);
+
+        let mut tiny = RecordRing::new([0u8; 3]);
+        assert_eq!(
+            tiny.push_record(b""),
+            Err(RecordTooLarge { len: 0, max: 0 })
+        );
+        assert!(tiny.is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+use std::string::String;
+use std::sync::{Mutex, MutexGuard, PoisonError, TryLockError};
+
+use super::fixed::Fixed;
+use super::{Box, Vec};
+
+/// Thread-safe ring of the most recent text records.
+///
+/// The shared backend of the flight-recorder integrations. Memory stays bounded
+/// by the capacity and the length of the individual records. A poisoned lock is
+/// ignored, since the records are most useful right after something panicked.
+#[derive(Debug)]
+pub struct Recorder {
+    records: Mutex<Fixed<Box<[Option<String>]>>>,
+}
+
+impl Recorder {
+    /// Creates a recorder keeping the last `capacity` records. Panics if
+    /// `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        let records = (0..capacity).map(|_| None).collect();
+        Recorder {
+            records: Mutex::new(records),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Stores `record`, evicting the oldest one if the recorder is full.
+    #[inline]
+    pub fn record(&self, record: String) {
+        self.lock().push(Some(record));
+    }
+
+    /// Copies out the stored records, oldest first.
+    pub fn dump(&self) -> Vec<String> {
+        self.lock().iter().flatten().cloned().collect()
+    }
+
+    /// Like `dump`, but returns `None` instead of blocking while another
+    /// thread holds the lock.
+    pub(crate) fn try_dump(&self) -> Option<Vec<String>> {
+        let records = match self.records.try_lock() {
+            Ok(records) => records,
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(records.iter().flatten().cloned().collect())
+    }
+
+    /// Takes the stored records, oldest first, leaving the recorder empty.
+    pub fn drain(&self) -> Vec<String> {
+        self.lock().iter_mut().filter_map(Option::take).collect()
+    }
+
+    #[inline]
+    pub fn clear(&self) {
+        self.lock().iter_mut().for_each(|record| *record = None);
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, Fixed<Box<[Option<String>]>>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keeps_latest() {
+        let recorder = Recorder::new(2);
+        recorder.record("a".into());
+        assert_eq!(recorder.dump(), ["a"]);
+        recorder.record("b".into());
+        recorder.record("c".into());
+        assert_eq!(recorder.dump(), ["b", "c"]);
+        assert_eq!(recorder.drain(), ["b", "c"]);
+        assert!(recorder.dump().is_empty());
+    }
+
+    #[test]
+    fn test_try_dump() {
+        let recorder = Recorder::new(2);
+        recorder.record("a".into());
+        assert_eq!(recorder.try_dump(), Some(vec!["a".into()]));
+        let _guard = recorder.lock();
+        assert_eq!(recorder.try_dump(), None);
+    }
+}
@@ -0,0 +1,122 @@
+//! Interpolation over the logical sequence of a numeric ring, for turning
+//! irregularly pushed samples into a fixed-rate series.
+
+use super::fixed::Fixed;
+use super::traits::Slice;
+use super::Vec;
+
+/// How to interpolate between neighbouring samples.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Catmull-Rom spline through the four nearest samples. Past either end,
+    /// the missing neighbour is extrapolated linearly.
+    Cubic,
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice,
+    S::Element: Copy + Into<f64>,
+{
+    /// Value at the fractional logical `index`, which is clamped to
+    /// `0.0..=(len - 1) as f64`.
+    pub fn sample_at(&self, index: f64, interpolation: Interpolation) -> f64 {
+        let last = self.len() - 1;
+        let index = index.clamp(0.0, last as f64);
+        let base = (index as usize).min(last);
+        let t = index - base as f64;
+        let at = |i: usize| -> f64 { (*self.get(i.min(last))).into() };
+
+        let (p1, p2) = (at(base), at(base + 1));
+        match interpolation {
+            Interpolation::Linear => p1 + (p2 - p1) * t,
+            Interpolation::Cubic => {
+                let p0 = if base == 0 {
+                    2.0 * p1 - p2
+                } else {
+                    at(base - 1)
+                };
+                let p3 = if base + 2 > last {
+                    2.0 * p2 - p1
+                } else {
+                    at(base + 2)
+                };
+                let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+                let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+                let c = -0.5 * p0 + 0.5 * p2;
+                ((a * t + b) * t + c) * t + p1
+            }
+        }
+    }
+
+    /// Fills `dst` with samples evenly spaced from the oldest element to the
+    /// newest one, both included.
+    pub fn resample_into(&self, dst: &mut [f64], interpolation: Interpolation) {
+        let step = match dst.len() {
+            0 | 1 => 0.0,
+            n => (self.len() - 1) as f64 / (n - 1) as f64,
+        };
+        for (i, sample) in dst.iter_mut().enumerate() {
+            *sample = self.sample_at(i as f64 * step, interpolation);
+        }
+    }
+
+    /// `n` samples evenly spaced from the oldest element to the newest one.
+    #[inline]
+    pub fn resample_to(&self, n: usize, interpolation: Interpolation) -> Vec<f64> {
+        let mut samples = Vec::new();
+        samples.resize(n, 0.0);
+        self.resample_into(&mut samples, interpolation);
+        samples
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample_at() {
+        let mut rb = Fixed::from([0i32; 4]);
+        for x in [10, 20, 40, 80, 160] {
+            rb.push(x);
+        }
+        // Logical order 20, 40, 80, 160.
+        assert_eq!(rb.sample_at(0.0, Interpolation::Linear), 20.0);
+        assert_eq!(rb.sample_at(1.5, Interpolation::Linear), 60.0);
+        assert_eq!(rb.sample_at(3.0, Interpolation::Linear), 160.0);
+        assert_eq!(rb.sample_at(-2.0, Interpolation::Linear), 20.0);
+        assert_eq!(rb.sample_at(9.0, Interpolation::Cubic), 160.0);
+        for i in 0..4 {
+            let expected = f64::from(*rb.get(i));
+            assert_eq!(rb.sample_at(i as f64, Interpolation::Cubic), expected);
+        }
+    }
+
+    #[test]
+    fn test_cubic_reproduces_lines() {
+        let rb = Fixed::from([1.0f32, 3.0, 5.0, 7.0, 9.0]);
+        for i in 0..=40 {
+            let x = i as f64 / 10.0;
+            let y = rb.sample_at(x, Interpolation::Cubic);
+            assert!((y - (1.0 + 2.0 * x)).abs() < 1e-9, "{} -> {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_resample() {
+        let rb = Fixed::from([0u8, 10, 20]);
+        assert_eq!(
+            rb.resample_to(5, Interpolation::Linear),
+            [0.0, 5.0, 10.0, 15.0, 20.0]
+        );
+        assert_eq!(rb.resample_to(2, Interpolation::Linear), [0.0, 20.0]);
+        assert_eq!(rb.resample_to(1, Interpolation::Cubic), [0.0]);
+        assert!(rb.resample_to(0, Interpolation::Linear).is_empty());
+
+        let single = Fixed::from([7u16]);
+        assert_eq!(single.resample_to(3, Interpolation::Cubic), [7.0; 3]);
+    }
+}
@@ -0,0 +1,109 @@
+use std::format;
+use std::string::String;
+use std::sync::Arc;
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use super::recorder::Recorder;
+use super::{Box, Vec};
+
+/// `log` backend that keeps the last records in a ring.
+///
+/// Records are formatted as `LEVEL target: message` into a shared `Recorder`,
+/// so memory stays bounded no matter how chatty the program is.
+#[derive(Clone, Debug)]
+pub struct RingLogger {
+    recorder: Arc<Recorder>,
+    level: LevelFilter,
+}
+
+impl RingLogger {
+    /// Creates a logger keeping the last `capacity` records up to `level`.
+    #[inline]
+    pub fn new(capacity: usize, level: LevelFilter) -> Self {
+        RingLogger {
+            recorder: Arc::new(Recorder::new(capacity)),
+            level,
+        }
+    }
+
+    /// Installs a new logger as the global `log` backend and returns it, so it
+    /// can be dumped later.
+    pub fn init(capacity: usize, level: LevelFilter) -> Result<&'static Self, SetLoggerError> {
+        let logger: &'static Self = Box::leak(Box::new(Self::new(capacity, level)));
+        log::set_logger(logger)?;
+        log::set_max_level(level);
+        Ok(logger)
+    }
+
+    #[inline]
+    pub fn level(&self) -> LevelFilter {
+        self.level
+    }
+
+    #[inline]
+    pub fn recorder(&self) -> &Arc<Recorder> {
+        &self.recorder
+    }
+
+    /// Copies out the stored records, oldest first.
+    #[inline]
+    pub fn dump(&self) -> Vec<String> {
+        self.recorder.dump()
+    }
+
+    /// Takes the stored records, oldest first.
+    #[inline]
+    pub fn drain(&self) -> Vec<String> {
+        self.recorder.drain()
+    }
+}
+
+impl Log for RingLogger {
+    #[inline]
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.recorder.record(format!(
+                "{} {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+    }
+
+    #[inline]
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use log::Level;
+
+    fn log(logger: &RingLogger, level: Level, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target("app")
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_keeps_last_records() {
+        let logger = RingLogger::new(2, LevelFilter::Info);
+        log(&logger, Level::Info, "one");
+        log(&logger, Level::Debug, "hidden");
+        log(&logger, Level::Warn, "two");
+        log(&logger, Level::Error, "three");
+        assert_eq!(logger.dump(), ["WARN app: two", "ERROR app: three"]);
+        assert_eq!(logger.drain().len(), 2);
+        assert!(logger.dump().is_empty());
+    }
+}
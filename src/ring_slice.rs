@@ -0,0 +1,308 @@
+//! Borrowed views over a logical subrange of a ring.
+
+use core::iter::Chain;
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+/// Elements of a logical subrange of a ring, in logical order.
+///
+/// The range may cross the physical end of the storage, so it is held as two
+/// slices; the second one is empty if it does not.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct RingSlice<'a, T> {
+    head: &'a [T],
+    tail: &'a [T],
+}
+
+impl<T> Clone for RingSlice<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RingSlice<'_, T> {}
+
+impl<'a, T> RingSlice<'a, T> {
+    #[inline]
+    pub(crate) fn new(head: &'a [T], tail: &'a [T]) -> Self {
+        RingSlice { head, tail }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.head.len() + self.tail.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Element at `index` within the view.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        match index.checked_sub(self.head.len()) {
+            None => self.head.get(index),
+            Some(index) => self.tail.get(index),
+        }
+    }
+
+    /// Splits the view into `..mid` and `mid..`. Panics if `mid > len()`, like
+    /// `slice::split_at`.
+    #[inline]
+    pub fn split_at(&self, mid: usize) -> (RingSlice<'a, T>, RingSlice<'a, T>) {
+        assert!(mid <= self.len(), "split point out of bounds");
+        match mid.checked_sub(self.head.len()) {
+            None => {
+                let (start, end) = self.head.split_at(mid);
+                (RingSlice::new(start, &[]), RingSlice::new(end, self.tail))
+            }
+            Some(mid) => {
+                let (start, end) = self.tail.split_at(mid);
+                (RingSlice::new(self.head, start), RingSlice::new(end, &[]))
+            }
+        }
+    }
+
+    /// The two physical pieces of the view, in logical order.
+    #[inline]
+    pub fn as_slices(&self) -> (&'a [T], &'a [T]) {
+        (self.head, self.tail)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Chain<slice::Iter<'a, T>, slice::Iter<'a, T>> {
+        self.head.iter().chain(self.tail.iter())
+    }
+
+    /// Copies the view into `dst`. Panics if the lengths differ.
+    #[inline]
+    pub fn copy_to_slice(&self, dst: &mut [T])
+    where
+        T: Copy,
+    {
+        assert_eq!(dst.len(), self.len(), "destination length differs");
+        let (head, tail) = dst.split_at_mut(self.head.len());
+        head.copy_from_slice(self.head);
+        tail.copy_from_slice(self.tail);
+    }
+}
+
+impl<'a, T> IntoIterator for RingSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &RingSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Mutable counterpart of `RingSlice`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct RingSliceMut<'a, T> {
+    head: &'a mut [T],
+    tail: &'a mut [T],
+}
+
+impl<'a, T> RingSliceMut<'a, T> {
+    #[inline]
+    pub(crate) fn new(head: &'a mut [T], tail: &'a mut [T]) -> Self {
+        RingSliceMut { head, tail }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.head.len() + self.tail.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Element at `index` within the view.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match index.checked_sub(self.head.len()) {
+            None => self.head.get(index),
+            Some(index) => self.tail.get(index),
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match index.checked_sub(self.head.len()) {
+            None => self.head.get_mut(index),
+            Some(index) => self.tail.get_mut(index),
+        }
+    }
+
+    /// Splits the view into `..mid` and `mid..`. Panics if `mid > len()`, like
+    /// `slice::split_at_mut`.
+    #[inline]
+    pub fn split_at_mut(self, mid: usize) -> (RingSliceMut<'a, T>, RingSliceMut<'a, T>) {
+        assert!(mid <= self.len(), "split point out of bounds");
+        match mid.checked_sub(self.head.len()) {
+            None => {
+                let (start, end) = self.head.split_at_mut(mid);
+                (
+                    RingSliceMut::new(start, &mut []),
+                    RingSliceMut::new(end, self.tail),
+                )
+            }
+            Some(mid) => {
+                let (start, end) = self.tail.split_at_mut(mid);
+                (
+                    RingSliceMut::new(self.head, start),
+                    RingSliceMut::new(end, &mut []),
+                )
+            }
+        }
+    }
+
+    /// The two physical pieces of the view, in logical order.
+    #[inline]
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        (self.head, self.tail)
+    }
+
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        (self.head, self.tail)
+    }
+
+    #[inline]
+    pub fn as_ring_slice(&self) -> RingSlice<'_, T> {
+        RingSlice::new(self.head, self.tail)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> Chain<slice::Iter<'_, T>, slice::Iter<'_, T>> {
+        self.head.iter().chain(self.tail.iter())
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> Chain<slice::IterMut<'_, T>, slice::IterMut<'_, T>> {
+        self.head.iter_mut().chain(self.tail.iter_mut())
+    }
+}
+
+impl<T> Index<usize> for RingSliceMut<'_, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        let len = self.len();
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {} out of bounds for view of length {}", index, len))
+    }
+}
+
+impl<T> IndexMut<usize> for RingSliceMut<'_, T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index {} out of bounds for view of length {}", index, len))
+    }
+}
+
+impl<'a, T> IntoIterator for RingSliceMut<'a, T> {
+    type Item = &'a mut T;
+    type IntoIter = Chain<slice::IterMut<'a, T>, slice::IterMut<'a, T>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.head.iter_mut().chain(self.tail.iter_mut())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Fixed;
+    use core::mem;
+
+    #[test]
+    fn test_range() {
+        let mut rb = Fixed::from([0; 6]);
+        for i in 1..=9 {
+            rb.push(i);
+        }
+        // Logical order 4..=9, physically split after 6.
+        let view = rb.range(1..5);
+        assert_eq!(view.as_slices(), (&[5, 6][..], &[7, 8][..]));
+        assert_eq!(view.get(2), Some(&7));
+        assert_eq!(view.get(4), None);
+        assert!(view.iter().eq(&[5, 6, 7, 8]));
+
+        let mut out = [0; 4];
+        view.copy_to_slice(&mut out);
+        assert_eq!(out, [5, 6, 7, 8]);
+
+        let (start, end) = view.split_at(1);
+        assert_eq!(start.as_slices(), (&[5][..], &[][..]));
+        assert_eq!(end.as_slices(), (&[6][..], &[7, 8][..]));
+        let (start, end) = view.split_at(3);
+        assert_eq!(start.as_slices(), (&[5, 6][..], &[7][..]));
+        assert_eq!(end.as_slices(), (&[8][..], &[][..]));
+        assert!(view.split_at(4).1.is_empty());
+
+        assert_eq!(rb.range(..2).as_slices(), (&[4, 5][..], &[][..]));
+        assert_eq!(rb.range(4..).as_slices(), (&[8, 9][..], &[][..]));
+        assert!(rb.range(3..3).is_empty());
+        assert_eq!(rb.range(..).len(), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_range_out_of_bounds() {
+        Fixed::from([0; 3]).range(1..4);
+    }
+
+    #[test]
+    fn test_split_at_logical_mut() {
+        let mut rb = Fixed::from([0; 5]);
+        rb.extend(1..=7);
+        // Logical order 3..=7, physically split after 5.
+        let (mut older, mut newer) = rb.split_at_logical_mut(3);
+        assert_eq!(older.as_slices(), (&[3, 4, 5][..], &[][..]));
+        assert_eq!(newer.len(), 2);
+        for (old, new) in older.iter_mut().zip(newer.iter_mut()) {
+            mem::swap(old, new);
+        }
+        older[2] *= 10;
+        newer[0] += 100;
+        assert_eq!(newer.get(2), None);
+        assert!(rb.iter().eq(&[6, 7, 50, 103, 4]));
+
+        let (start, end) = rb.split_at_logical_mut(4);
+        assert_eq!(
+            start.as_ring_slice().as_slices(),
+            (&[6, 7, 50][..], &[103][..])
+        );
+        let (middle, _) = start.split_at_mut(2);
+        assert!(middle.into_iter().eq(&mut [6, 7]));
+        assert!(end.iter().eq(&[4]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ring_slice_mut_index_out_of_bounds() {
+        let mut rb = Fixed::from([0; 3]);
+        let (_, newer) = rb.split_at_logical_mut(1);
+        let _ = newer[2];
+    }
+}
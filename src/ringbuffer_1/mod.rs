@@ -1,20 +1,9 @@
-#![cfg_attr(not(feature = "std"), no_std)]
-#![allow(dead_code)]
-#![allow(unused_imports)]
+//! Former home of `Fixed`, kept as a deprecated alias of the top-level one.
 
-#[cfg(not(feature = "std"))]
-extern crate alloc;
+pub mod ringbuffer_fixed {
+    // Traits cannot be deprecated through a re-export.
+    pub use crate::traits::{Slice, SliceMut};
 
-#[cfg(not(feature = "std"))]
-pub(self) use alloc::vec::Vec;
-#[cfg(feature = "std")]
-pub(self) use std::vec::Vec;
-
-#[cfg(not(feature = "std"))]
-pub(self) use alloc::boxed::Box;
-#[cfg(feature = "std")]
-pub(self) use std::boxed::Box;
-
-mod traits;
-
-pub mod ringbuffer_fixed;
+    #[deprecated(note = "use `rust_ringbuf::Fixed`")]
+    pub type Fixed<S> = crate::fixed::Fixed<S>;
+}
@@ -0,0 +1,166 @@
+use core::ops::Range;
+
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// History of per-frame states for rollback netcode.
+///
+/// The newest frame lives at the last logical index and frame `f` at
+/// `len - 1 - (newest - f)`. Frames that were never inserted inside the
+/// retained range are simply empty slots.
+#[derive(Clone, Debug)]
+pub struct RollbackRing<S> {
+    states: Fixed<S>,
+    newest: Option<u64>,
+}
+
+impl<S, T> RollbackRing<S>
+where
+    S: SliceMut<Element = Option<T>>,
+{
+    /// Creates a history over `storage`, which is cleared.
+    pub fn new(storage: S) -> Self {
+        let mut states = Fixed::from(storage);
+        states.iter_mut().for_each(|state| *state = None);
+        RollbackRing {
+            states,
+            newest: None,
+        }
+    }
+
+    /// Number of consecutive frames the history can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.states.len()
+    }
+
+    #[inline]
+    pub fn latest_frame(&self) -> Option<u64> {
+        self.newest
+    }
+
+    /// Oldest frame number still covered by the history.
+    #[inline]
+    pub fn oldest_frame(&self) -> Option<u64> {
+        self.newest
+            .map(|newest| newest.saturating_sub(self.capacity() as u64 - 1))
+    }
+
+    /// Stores the state of `frame`.
+    ///
+    /// Frames after the latest one advance the history, evicting the oldest
+    /// frames; frames inside it replace the stored state. Returns the state that
+    /// was replaced or evicted last, or gives `state` back if `frame` is older
+    /// than the history.
+    pub fn insert(&mut self, frame: u64, state: T) -> Result<Option<T>, T> {
+        let gap = match self.newest {
+            Some(newest) if frame <= newest => {
+                return match self.index_of(frame) {
+                    Some(index) => Ok(self.states[index].replace(state)),
+                    None => Err(state),
+                };
+            }
+            Some(newest) => (frame - newest - 1).min(self.capacity() as u64),
+            None => 0,
+        };
+
+        let mut evicted = None;
+        for _ in 0..gap {
+            evicted = self.states.push(None).or(evicted);
+        }
+        evicted = self.states.push(Some(state)).or(evicted);
+        self.newest = Some(frame);
+        Ok(evicted)
+    }
+
+    #[inline]
+    pub fn get(&self, frame: u64) -> Option<&T> {
+        let index = self.index_of(frame)?;
+        self.states[index].as_ref()
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, frame: u64) -> Option<&mut T> {
+        let index = self.index_of(frame)?;
+        self.states[index].as_mut()
+    }
+
+    /// Makes `frame` the latest frame again, discarding every newer state.
+    ///
+    /// Returns the range of discarded frame numbers, or `None` if `frame` is
+    /// not covered by the history.
+    pub fn rollback_to(&mut self, frame: u64) -> Option<Range<u64>> {
+        let newest = self.newest?;
+        self.index_of(frame)?;
+
+        let discarded = (newest - frame) as usize;
+        let len = self.capacity();
+        for index in len - discarded..len {
+            self.states[index] = None;
+        }
+        self.states.rotate_right(discarded);
+        self.newest = Some(frame);
+        Some(frame + 1..newest + 1)
+    }
+
+    /// Drops every stored state.
+    pub fn clear(&mut self) {
+        self.states.iter_mut().for_each(|state| *state = None);
+        self.newest = None;
+    }
+
+    fn index_of(&self, frame: u64) -> Option<usize> {
+        let back = self.newest?.checked_sub(frame)?;
+        let len = self.capacity() as u64;
+        (back < len).then(|| (len - 1 - back) as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut ring = RollbackRing::new([None; 4]);
+        for frame in 1..=6 {
+            ring.insert(frame, frame * 10).unwrap();
+        }
+        assert_eq!(ring.oldest_frame(), Some(3));
+        assert_eq!(ring.get(2), None);
+        assert_eq!(ring.get(3), Some(&30));
+        assert_eq!(ring.get(6), Some(&60));
+        assert_eq!(ring.get(7), None);
+        assert_eq!(ring.insert(4, 41), Ok(Some(40)));
+        assert_eq!(ring.insert(1, 0), Err(0));
+    }
+
+    #[test]
+    fn test_gaps() {
+        let mut ring = RollbackRing::new(vec![None; 4]);
+        ring.insert(0, 'a').unwrap();
+        ring.insert(2, 'c').unwrap();
+        assert_eq!(ring.get(1), None);
+        assert_eq!(ring.get(0), Some(&'a'));
+        assert_eq!(ring.insert(100, 'z'), Ok(Some('c')));
+        assert_eq!(ring.get(0), None);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut ring = RollbackRing::new([None; 4]);
+        for frame in 10..14 {
+            ring.insert(frame, frame).unwrap();
+        }
+        assert_eq!(ring.rollback_to(11), Some(12..14));
+        assert_eq!(ring.latest_frame(), Some(11));
+        assert_eq!(ring.get(12), None);
+        assert_eq!(ring.get(10), Some(&10));
+        ring.insert(12, 120).unwrap();
+        ring.insert(13, 130).unwrap();
+        assert_eq!(ring.get(10), Some(&10));
+        assert_eq!(ring.get(13), Some(&130));
+        assert_eq!(ring.rollback_to(5), None);
+        assert_eq!(ring.rollback_to(13), Some(14..14));
+    }
+}
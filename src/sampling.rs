@@ -0,0 +1,23 @@
+/// Source of uniformly distributed random numbers used by the sampling APIs.
+///
+/// Implemented for any `FnMut() -> u64`, so a generator from another crate can
+/// be plugged in with a closure such as `|| rng.next_u64()`.
+pub trait RandomSource {
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a number in `0..bound`. `bound` must not be zero.
+    #[inline]
+    fn below(&mut self, bound: usize) -> usize {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+impl<F> RandomSource for F
+where
+    F: FnMut() -> u64,
+{
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self()
+    }
+}
@@ -0,0 +1,191 @@
+use alloc::collections::VecDeque;
+
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+use super::Vec;
+
+/// Line history with both a line-count and a byte budget.
+///
+/// Line contents live in a byte ring whose length is the byte budget; the
+/// lines themselves are spans into it, keyed by absolute byte position. Pushing
+/// a line evicts whole oldest lines until both limits are met again.
+#[derive(Clone, Debug)]
+pub struct Scrollback<S> {
+    bytes: Fixed<S>,
+    lines: VecDeque<(u64, usize)>,
+    max_lines: usize,
+    written: u64,
+}
+
+impl<S> Scrollback<S>
+where
+    S: SliceMut<Element = u8>,
+{
+    /// Creates a scrollback keeping at most `max_lines` lines within the length
+    /// of `storage` in bytes. Panics if either limit is zero.
+    pub fn new(storage: S, max_lines: usize) -> Self {
+        assert!(max_lines > 0);
+        Scrollback {
+            bytes: Fixed::from(storage),
+            lines: VecDeque::new(),
+            max_lines,
+            written: 0,
+        }
+    }
+
+    #[inline]
+    pub fn max_lines(&self) -> usize {
+        self.max_lines
+    }
+
+    #[inline]
+    pub fn byte_budget(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Number of lines held.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Number of bytes used by the lines held.
+    #[inline]
+    pub fn bytes_used(&self) -> usize {
+        self.lines.iter().map(|&(_, len)| len).sum()
+    }
+
+    /// Appends a line. A line longer than the byte budget keeps only its tail.
+    pub fn push_line(&mut self, line: &[u8]) {
+        let budget = self.bytes.len();
+        let line = &line[line.len().saturating_sub(budget)..];
+        self.bytes.extend_from_slice(line);
+
+        let start = self.written;
+        self.written += line.len() as u64;
+        let oldest_kept = self.written.saturating_sub(budget as u64);
+        while let Some(&(line_start, _)) = self.lines.front() {
+            if line_start >= oldest_kept && self.lines.len() < self.max_lines {
+                break;
+            }
+            self.lines.pop_front();
+        }
+        self.lines.push_back((start, line.len()));
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, line: &str) {
+        self.push_line(line.as_bytes());
+    }
+
+    /// Line `index`, counting from the oldest one held.
+    pub fn get(&self, index: usize) -> Option<Line<'_>> {
+        let &(start, len) = self.lines.get(index)?;
+        let offset = self.bytes.len() - (self.written - start) as usize;
+        let (a, b) = self.bytes.slices();
+        let line = if offset >= a.len() {
+            let offset = offset - a.len();
+            Line(&b[offset..offset + len], &[])
+        } else if offset + len <= a.len() {
+            Line(&a[offset..offset + len], &[])
+        } else {
+            Line(&a[offset..], &b[..offset + len - a.len()])
+        };
+        Some(line)
+    }
+
+    /// Lines from the oldest to the newest.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Line<'_>> + '_ {
+        (0..self.lines.len()).filter_map(move |index| self.get(index))
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+/// One line of a `Scrollback`, possibly split across the seam of the ring.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Line<'a>(&'a [u8], &'a [u8]);
+
+impl<'a> Line<'a> {
+    #[inline]
+    pub fn as_slices(&self) -> (&'a [u8], &'a [u8]) {
+        (self.0, self.1)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len() + self.1.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + 'a {
+        self.0.iter().chain(self.1).copied()
+    }
+
+    #[inline]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut line = Vec::with_capacity(self.len());
+        line.extend_from_slice(self.0);
+        line.extend_from_slice(self.1);
+        line
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lines<S: SliceMut<Element = u8>>(sb: &Scrollback<S>) -> Vec<Vec<u8>> {
+        sb.iter().map(|line| line.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_line_limit() {
+        let mut sb = Scrollback::new([0u8; 64], 2);
+        sb.push_str("one");
+        sb.push_str("two");
+        sb.push_str("three");
+        assert_eq!(lines(&sb), [&b"two"[..], b"three"]);
+        assert_eq!(sb.bytes_used(), 8);
+    }
+
+    #[test]
+    fn test_byte_budget() {
+        let mut sb = Scrollback::new(vec![0u8; 10], 100);
+        sb.push_str("abcd");
+        sb.push_str("efg");
+        sb.push_str("hij");
+        assert_eq!(sb.len(), 3);
+        sb.push_str("kl");
+        assert_eq!(lines(&sb), [&b"efg"[..], b"hij", b"kl"]);
+        let wrapped = sb.get(2).unwrap();
+        assert_eq!(wrapped.len(), 2);
+        sb.push_str("a line that is way too long");
+        assert_eq!(lines(&sb), [&b"y too long"[..]]);
+    }
+
+    #[test]
+    fn test_wrapped_line() {
+        let mut sb = Scrollback::new([0u8; 8], 8);
+        sb.push_str("12345");
+        sb.push_str("6789");
+        let line = sb.get(0).unwrap();
+        assert_eq!(line.to_vec(), b"6789");
+        assert_eq!(line.as_slices(), (&b"678"[..], &b"9"[..]));
+        assert_eq!(line.bytes().count(), 4);
+    }
+}
@@ -0,0 +1,359 @@
+//! Fill and search over both halves of a ring, in logical order.
+//!
+//! Byte rings use `memchr` under the `simd` feature and scalar loops
+//! otherwise; the results are the same either way.
+
+use core::cmp::Ordering;
+
+use super::fixed::Fixed;
+use super::traits::{Slice, SliceMut};
+
+impl<S> Fixed<S>
+where
+    S: SliceMut,
+{
+    /// Sets every element to `value`, keeping `first`.
+    #[inline]
+    pub fn fill(&mut self, value: S::Element)
+    where
+        S::Element: Clone,
+    {
+        self.storage_mut().fill(value);
+    }
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice,
+{
+    #[inline]
+    pub fn contains(&self, x: &S::Element) -> bool
+    where
+        S::Element: PartialEq,
+    {
+        let (head, tail) = self.slices();
+        head.contains(x) || tail.contains(x)
+    }
+
+    /// Logical index of the oldest element matching `predicate`.
+    #[inline]
+    pub fn position<P>(&self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&S::Element) -> bool,
+    {
+        let (head, tail) = self.slices();
+        match head.iter().position(&mut predicate) {
+            Some(index) => Some(index),
+            None => Some(head.len() + tail.iter().position(predicate)?),
+        }
+    }
+
+    /// Logical index of the newest element matching `predicate`.
+    #[inline]
+    pub fn rposition<P>(&self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&S::Element) -> bool,
+    {
+        let (head, tail) = self.slices();
+        match tail.iter().rposition(&mut predicate) {
+            Some(index) => Some(head.len() + index),
+            None => head.iter().rposition(predicate),
+        }
+    }
+}
+
+/// Binary searches over logical order. Like the slice methods, they assume
+/// the ring is sorted, or at least partitioned, in logical order; otherwise
+/// the result is unspecified.
+impl<S> Fixed<S>
+where
+    S: Slice,
+{
+    /// Whether the elements are non-decreasing in logical order.
+    #[inline]
+    pub fn is_sorted(&self) -> bool
+    where
+        S::Element: PartialOrd,
+    {
+        self.is_sorted_by(|a, b| a <= b)
+    }
+
+    /// Whether `compare` holds for every pair of logically adjacent elements,
+    /// like `slice::is_sorted_by`.
+    pub fn is_sorted_by<F>(&self, mut compare: F) -> bool
+    where
+        F: FnMut(&S::Element, &S::Element) -> bool,
+    {
+        let (head, tail) = self.slices();
+        let seam = match (head.last(), tail.first()) {
+            (Some(a), Some(b)) => compare(a, b),
+            _ => true,
+        };
+        seam && head.is_sorted_by(&mut compare) && tail.is_sorted_by(compare)
+    }
+
+    #[inline]
+    pub fn is_sorted_by_key<K, F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&S::Element) -> K,
+        K: PartialOrd,
+    {
+        self.is_sorted_by(|a, b| f(a) <= f(b))
+    }
+
+    #[inline]
+    pub fn binary_search(&self, x: &S::Element) -> Result<usize, usize>
+    where
+        S::Element: Ord,
+    {
+        self.binary_search_by(|element| element.cmp(x))
+    }
+
+    /// Searches with `f` returning how an element compares to the target.
+    /// Returns the logical index of a match, or where the target would be
+    /// inserted to keep the order.
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&S::Element) -> Ordering,
+    {
+        let (head, tail) = self.slices();
+        // `head` is never empty; the target is in it unless its last element
+        // is still smaller.
+        match head.last().map(&mut f) {
+            Some(Ordering::Less) => match tail.binary_search_by(f) {
+                Ok(index) => Ok(head.len() + index),
+                Err(index) => Err(head.len() + index),
+            },
+            _ => head.binary_search_by(f),
+        }
+    }
+
+    #[inline]
+    pub fn binary_search_by_key<B, F>(&self, key: &B, mut f: F) -> Result<usize, usize>
+    where
+        B: Ord,
+        F: FnMut(&S::Element) -> B,
+    {
+        self.binary_search_by(|element| f(element).cmp(key))
+    }
+
+    /// Logical index of the first element for which `pred` is false, given
+    /// that it is true for a prefix and false for the rest. For a timestamp
+    /// ring, `partition_point(|&t| t <= since)` is the first newer element.
+    pub fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(&S::Element) -> bool,
+    {
+        let (head, tail) = self.slices();
+        match head.last().map(&mut pred) {
+            Some(false) => head.partition_point(pred),
+            _ => head.len() + tail.partition_point(pred),
+        }
+    }
+}
+
+impl<S> Fixed<S>
+where
+    S: Slice<Element = u8>,
+{
+    /// Logical index of the oldest occurrence of `byte`.
+    #[inline]
+    pub fn find_byte(&self, byte: u8) -> Option<usize> {
+        let (head, tail) = self.slices();
+        position(byte, head).or_else(|| Some(head.len() + position(byte, tail)?))
+    }
+
+    /// Logical index of the newest occurrence of `byte`.
+    #[inline]
+    pub fn rfind_byte(&self, byte: u8) -> Option<usize> {
+        let (head, tail) = self.slices();
+        match rposition(byte, tail) {
+            Some(index) => Some(head.len() + index),
+            None => rposition(byte, head),
+        }
+    }
+
+    /// Logical index of the oldest occurrence of `needle`, which may cross
+    /// the physical end of the storage. An empty needle is found at 0.
+    pub fn find_bytes(&self, needle: &[u8]) -> Option<usize> {
+        let (head, tail) = self.slices();
+        if needle.is_empty() {
+            return Some(0);
+        }
+        if let Some(index) = find(head, needle) {
+            return Some(index);
+        }
+        // Starts in the last `needle.len() - 1` bytes of `head` that run on
+        // into `tail`.
+        let straddle = head.len().saturating_sub(needle.len() - 1)..head.len();
+        for start in straddle {
+            let (in_head, in_tail) = needle.split_at(head.len() - start);
+            if head[start..] == *in_head && tail.starts_with(in_tail) {
+                return Some(start);
+            }
+        }
+        find(tail, needle).map(|index| head.len() + index)
+    }
+}
+
+#[cfg(feature = "simd")]
+#[inline]
+fn position(byte: u8, haystack: &[u8]) -> Option<usize> {
+    memchr::memchr(byte, haystack)
+}
+
+#[cfg(not(feature = "simd"))]
+#[inline]
+fn position(byte: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == byte)
+}
+
+#[cfg(feature = "simd")]
+#[inline]
+fn rposition(byte: u8, haystack: &[u8]) -> Option<usize> {
+    memchr::memrchr(byte, haystack)
+}
+
+#[cfg(not(feature = "simd"))]
+#[inline]
+fn rposition(byte: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|&b| b == byte)
+}
+
+#[cfg(feature = "simd")]
+#[inline]
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    memchr::memmem::find(haystack, needle)
+}
+
+#[cfg(not(feature = "simd"))]
+#[inline]
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes(first: usize, data: &[u8]) -> Fixed<Vec<u8>> {
+        let mut rb = Fixed::from(data.to_vec());
+        rb.set_first(first);
+        rb
+    }
+
+    #[test]
+    fn test_fill_and_contains() {
+        let mut rb = Fixed::from([1, 2, 3]);
+        rb.push(4);
+        assert!(rb.contains(&4));
+        assert!(!rb.contains(&1));
+        rb.fill(7);
+        assert_eq!(rb.first(), 1);
+        assert!(rb.iter().all(|&x| x == 7));
+    }
+
+    #[test]
+    fn test_position() {
+        let mut rb = Fixed::from([0; 5]);
+        rb.extend(1..=7);
+        // Logical order 3..=7, physically split after 5.
+        assert_eq!(rb.position(|&x| x % 2 == 0), Some(1));
+        assert_eq!(rb.position(|&x| x > 5), Some(3));
+        assert_eq!(rb.position(|&x| x > 7), None);
+        assert_eq!(rb.rposition(|&x| x % 2 == 0), Some(3));
+        assert_eq!(rb.rposition(|&x| x < 5), Some(1));
+        assert_eq!(rb.rposition(|&x| x < 3), None);
+
+        let mut calls = 0;
+        rb.position(|&x| {
+            calls += 1;
+            x == 4
+        });
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_is_sorted() {
+        let mut rb = Fixed::from([0; 4]);
+        rb.extend([9, 5, 1, 2, 3]);
+        assert_eq!(rb.first(), 1);
+        assert!(!rb.is_sorted());
+        rb.push(4);
+        // 1, 2, 3, 4, physically split after 2.
+        assert!(rb.is_sorted());
+        assert!(!rb.is_sorted_by(|a, b| a < b && b - a > 1));
+        assert!(rb.is_sorted_by_key(|&x| x / 2));
+        rb.push(0);
+        assert!(!rb.is_sorted());
+        assert!(Fixed::from([f64::NAN]).is_sorted());
+        assert!(!Fixed::from([1.0, f64::NAN]).is_sorted());
+    }
+
+    #[test]
+    fn test_binary_search() {
+        let mut rb = Fixed::from(vec![0u64; 6]);
+        rb.extend([10, 20, 20, 30, 40, 50, 60, 70]);
+        // Logical order 20, 30, 40, 50, 60, 70, physically split after 50.
+        let logical: Vec<u64> = rb.iter().copied().collect();
+        for x in 0..80 {
+            assert_eq!(
+                rb.binary_search(&x).is_ok(),
+                logical.binary_search(&x).is_ok()
+            );
+            assert_eq!(
+                rb.partition_point(|&t| t <= x),
+                logical.partition_point(|&t| t <= x),
+                "{}",
+                x
+            );
+        }
+        assert_eq!(rb.binary_search(&60), Ok(4));
+        assert_eq!(rb.binary_search(&45), Err(3));
+        assert_eq!(rb.binary_search(&55), Err(4));
+        assert_eq!(rb.binary_search(&99), Err(6));
+        assert_eq!(rb.binary_search_by_key(&7, |&t| t / 10), Ok(5));
+    }
+
+    #[test]
+    fn test_find_byte() {
+        // Logical order "cdeab", physically split after "cde".
+        let rb = bytes(2, b"abcde");
+        assert_eq!(rb.find_byte(b'c'), Some(0));
+        assert_eq!(rb.find_byte(b'a'), Some(3));
+        assert_eq!(rb.find_byte(b'z'), None);
+
+        let rb = bytes(1, b"xaxbx");
+        assert_eq!(rb.find_byte(b'x'), Some(1));
+        assert_eq!(rb.rfind_byte(b'x'), Some(4));
+        assert_eq!(rb.rfind_byte(b'a'), Some(0));
+        assert_eq!(rb.rfind_byte(b'z'), None);
+    }
+
+    #[test]
+    fn test_find_bytes() {
+        // Logical order "defgabc".
+        let rb = bytes(3, b"abcdefg");
+        assert_eq!(rb.find_bytes(b""), Some(0));
+        assert_eq!(rb.find_bytes(b"ef"), Some(1));
+        assert_eq!(rb.find_bytes(b"gab"), Some(3));
+        assert_eq!(rb.find_bytes(b"fgabc"), Some(2));
+        assert_eq!(rb.find_bytes(b"bc"), Some(5));
+        assert_eq!(rb.find_bytes(b"defgabc"), Some(0));
+        assert_eq!(rb.find_bytes(b"cd"), None);
+        assert_eq!(rb.find_bytes(b"defgabcd"), None);
+
+        for first in 0..7 {
+            let rb = bytes(first, b"abcdefg");
+            let logical: Vec<u8> = rb.iter().copied().collect();
+            for start in 0..7 {
+                for end in start + 1..=7 {
+                    let needle = &logical[start..end];
+                    assert_eq!(rb.find_bytes(needle), Some(start), "{:?}", needle);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Serde support for `Fixed`.
+//!
+//! A ring is serialized as the plain sequence of its elements in logical
+//! order (oldest first), independent of the storage type and of the internal
+//! rotation. When deserializing, the length of the sequence may differ from the
+//! capacity of the target ring; a `CapacityPolicy` decides what happens then.
+//! The plain `Deserialize` impl uses `CapacityPolicy::Error` for storages with
+//! a length fixed by their type and takes the sequence length otherwise; use
+//! `FixedSeed` to pick the capacity and policy explicitly.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, DeserializeSeed, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::fixed::Fixed;
+use super::traits::{FromElements, Slice};
+use super::Vec;
+
+/// What to do when a serialized ring does not match the target capacity.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CapacityPolicy {
+    /// Drop the oldest elements until the rest fits.
+    TruncateOldest,
+    /// Fail the deserialization.
+    #[default]
+    Error,
+    /// Use the serialized length as the capacity. Only possible for storage
+    /// without a length fixed by its type (`Vec`, `Box<[T]>`); fails otherwise.
+    Grow,
+}
+
+impl<S> Serialize for Fixed<S>
+where
+    S: Slice,
+    S::Element: Serialize,
+{
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for item in self.iter() {
+            seq.serialize_element(item)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, S> Deserialize<'de> for Fixed<S>
+where
+    S: FromElements,
+    S::Element: Deserialize<'de>,
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FixedSeed::new(S::FIXED_LEN, CapacityPolicy::Error).deserialize(deserializer)
+    }
+}
+
+/// Deserializes a `Fixed` ring with an explicit capacity and `CapacityPolicy`.
+#[derive(Debug)]
+pub struct FixedSeed<S> {
+    capacity: Option<usize>,
+    policy: CapacityPolicy,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<S> FixedSeed<S>
+where
+    S: FromElements,
+{
+    /// `capacity` is the length of the ring to build; `None` means the
+    /// length fixed by the storage type, or the serialized length if the type
+    /// does not fix one.
+    #[inline]
+    pub fn new(capacity: Option<usize>, policy: CapacityPolicy) -> Self {
+        FixedSeed {
+            capacity: capacity.or(S::FIXED_LEN),
+            policy,
+            _marker: PhantomData,
+        }
+    }
+
+    fn build<E: de::Error>(&self, mut elements: Vec<S::Element>) -> Result<Fixed<S>, E> {
+        let len = elements.len();
+        let capacity = match self.capacity {
+            Some(capacity) if capacity == len => capacity,
+            Some(capacity) if len > capacity && self.policy == CapacityPolicy::TruncateOldest => {
+                elements.drain(..len - capacity);
+                capacity
+            }
+            Some(_) if self.policy == CapacityPolicy::Grow && S::FIXED_LEN.is_none() => len,
+            Some(capacity) => {
+                return Err(E::invalid_length(len, &CapacityExpected(capacity)));
+            }
+            None => len,
+        };
+        if capacity == 0 {
+            return Err(E::invalid_length(0, &"a non-empty ring"));
+        }
+        S::from_elements(elements)
+            .map(Fixed::from)
+            .ok_or_else(|| E::invalid_length(capacity, &"a length fitting the storage"))
+    }
+}
+
+impl<'de, S> DeserializeSeed<'de> for FixedSeed<S>
+where
+    S: FromElements,
+    S::Element: Deserialize<'de>,
+{
+    type Value = Fixed<S>;
+
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Fixed<S>, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, S> Visitor<'de> for FixedSeed<S>
+where
+    S: FromElements,
+    S::Element: Deserialize<'de>,
+{
+    type Value = Fixed<S>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of ring elements")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Fixed<S>, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(item) = seq.next_element()? {
+            elements.push(item);
+        }
+        self.build(elements)
+    }
+}
+
+struct CapacityExpected(usize);
+
+impl de::Expected for CapacityExpected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} elements", self.0)
+    }
+}
+
+#[cfg(all(test, feature = "postcard"))]
+mod test {
+    use super::*;
+
+    fn encode<S>(rb: &Fixed<S>) -> Vec<u8>
+    where
+        S: Slice,
+        S::Element: Serialize,
+    {
+        let mut buf = [0u8; 64];
+        postcard::to_slice(rb, &mut buf).unwrap().to_vec()
+    }
+
+    fn decode<S>(bytes: &[u8], capacity: Option<usize>, policy: CapacityPolicy) -> Option<Fixed<S>>
+    where
+        S: FromElements,
+        S::Element: for<'de> Deserialize<'de>,
+    {
+        let mut de = postcard::Deserializer::from_bytes(bytes);
+        FixedSeed::new(capacity, policy).deserialize(&mut de).ok()
+    }
+
+    #[test]
+    fn test_layout_independent() {
+        let mut rotated = Fixed::from([0u8; 3]);
+        rotated.extend([1, 2, 3, 4]);
+        let plain = Fixed::from(vec![2u8, 3, 4]);
+        assert_eq!(encode(&rotated), encode(&plain));
+        let back: Fixed<Box<[u8]>> = postcard::from_bytes(&encode(&rotated)).unwrap();
+        assert_eq!(back.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+    }
+
+    #[test]
+    fn test_capacity_policies() {
+        let bytes = encode(&Fixed::from(vec![1u8, 2, 3, 4]));
+        assert!(postcard::from_bytes::<Fixed<[u8; 3]>>(&bytes).is_err());
+
+        let truncated: Fixed<[u8; 3]> =
+            decode(&bytes, None, CapacityPolicy::TruncateOldest).unwrap();
+        assert_eq!(truncated.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+        assert!(decode::<[u8; 3]>(&bytes, None, CapacityPolicy::Grow).is_none());
+
+        let grown: Fixed<Vec<u8>> = decode(&bytes, Some(2), CapacityPolicy::Grow).unwrap();
+        assert_eq!(grown.len(), 4);
+        assert!(decode::<Vec<u8>>(&bytes, Some(2), CapacityPolicy::Error).is_none());
+        assert!(decode::<Vec<u8>>(&bytes, Some(8), CapacityPolicy::TruncateOldest).is_none());
+    }
+}
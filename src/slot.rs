@@ -0,0 +1,61 @@
+//! In-bounds element slots, the one place ring writes skip bounds checks.
+
+use core::mem;
+
+/// A slot of a borrowed slice whose index is known to be in bounds.
+///
+/// The index is checked once when the slot is made; the slice is borrowed for
+/// the slot's lifetime, so it cannot shrink in between and the accesses can
+/// skip the check.
+pub(crate) struct Slot<'a, T> {
+    data: &'a mut [T],
+    index: usize,
+}
+
+impl<'a, T> Slot<'a, T> {
+    /// Slot `index` of `data`, or `None` if it is out of bounds.
+    #[inline]
+    pub(crate) fn new(data: &'a mut [T], index: usize) -> Option<Self> {
+        (index < data.len()).then_some(Slot { data, index })
+    }
+
+    #[inline]
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    #[inline]
+    pub(crate) fn into_mut(self) -> &'a mut T {
+        // Safety: `index < data.len()` was checked in `new`, and `data` has
+        // been borrowed exclusively since.
+        unsafe { self.data.get_unchecked_mut(self.index) }
+    }
+
+    /// Stores `item` and returns the previous element.
+    #[inline]
+    pub(crate) fn replace(self, item: T) -> T {
+        mem::replace(self.into_mut(), item)
+    }
+
+    /// Stores `item`, dropping the previous element in place.
+    #[inline]
+    pub(crate) fn set(self, item: T) {
+        *self.into_mut() = item;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slot() {
+        let mut data = [1, 2, 3];
+        assert!(Slot::new(&mut data, 3).is_none());
+        let slot = Slot::new(&mut data, 2).unwrap();
+        assert_eq!(slot.index(), 2);
+        assert_eq!(slot.replace(9), 3);
+        Slot::new(&mut data, 0).unwrap().set(7);
+        assert_eq!(data, [7, 2, 9]);
+    }
+}
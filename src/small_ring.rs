@@ -0,0 +1,151 @@
+use core::array;
+use core::hash::{Hash, Hasher};
+use core::iter;
+
+use super::fixed::Fixed;
+use super::traits::{Slice, SliceMut};
+use super::{Box, Vec};
+
+/// `Fixed` ring over `SmallStorage`.
+pub type SmallRing<T, const N: usize> = Fixed<SmallStorage<T, N>>;
+
+/// Ring storage that lives inline for up to `N` elements and in a boxed slice
+/// beyond that, like `SmallVec`.
+///
+/// Short-lived small windows then need no allocation at all, while the
+/// length can still be chosen at run time. A ring never changes length, so
+/// the choice is made once, on creation.
+#[derive(Clone, Debug)]
+pub struct SmallStorage<T, const N: usize> {
+    repr: Repr<T, N>,
+}
+
+#[derive(Clone, Debug)]
+enum Repr<T, const N: usize> {
+    /// The first `len` elements of `data`; the rest are unused.
+    Inline {
+        data: [T; N],
+        len: usize,
+    },
+    Heap(Box<[T]>),
+}
+
+impl<T, const N: usize> SmallStorage<T, N> {
+    /// `len` copies of `value`, inline if `len` is at most `N`.
+    pub fn from_elem(value: T, len: usize) -> Self
+    where
+        T: Clone,
+    {
+        let repr = if len <= N {
+            Repr::Inline {
+                data: array::from_fn(|_| value.clone()),
+                len,
+            }
+        } else {
+            Repr::Heap(iter::repeat_n(value, len).collect())
+        };
+        SmallStorage { repr }
+    }
+
+    /// Takes the elements of `vec`, moving them inline if there are at most
+    /// `N`.
+    pub fn from_vec(vec: Vec<T>) -> Self
+    where
+        T: Default,
+    {
+        let len = vec.len();
+        let repr = if len <= N {
+            let mut elements = vec.into_iter();
+            Repr::Inline {
+                data: array::from_fn(|_| elements.next().unwrap_or_default()),
+                len,
+            }
+        } else {
+            Repr::Heap(vec.into_boxed_slice())
+        };
+        SmallStorage { repr }
+    }
+
+    /// Whether the elements live on the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        matches!(self.repr, Repr::Heap(_))
+    }
+}
+
+impl<T, const N: usize> Slice for SmallStorage<T, N> {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[T] {
+        match &self.repr {
+            Repr::Inline { data, len } => &data[..*len],
+            Repr::Heap(data) => data,
+        }
+    }
+}
+
+impl<T, const N: usize> SliceMut for SmallStorage<T, N> {
+    #[inline]
+    fn slice_mut(&mut self) -> &mut [T] {
+        match &mut self.repr {
+            Repr::Inline { data, len } => &mut data[..*len],
+            Repr::Heap(data) => data,
+        }
+    }
+}
+
+impl<T, const N: usize> PartialEq for SmallStorage<T, N>
+where
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.slice() == other.slice()
+    }
+}
+
+impl<T, const N: usize> Eq for SmallStorage<T, N> where T: Eq {}
+
+impl<T, const N: usize> Hash for SmallStorage<T, N>
+where
+    T: Hash,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.slice().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_inline_and_spilled() {
+        let mut small: SmallRing<u32, 4> = Fixed::from(SmallStorage::from_elem(0, 3));
+        assert!(!small.storage().spilled());
+        small.extend(1..=5);
+        assert!(small.iter().eq(&[3, 4, 5]));
+
+        let mut large: SmallRing<u32, 4> = Fixed::from(SmallStorage::from_elem(0, 6));
+        assert!(large.storage().spilled());
+        large.extend(1..=7);
+        assert!(large.iter().eq(&[2, 3, 4, 5, 6, 7]));
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let inline = SmallStorage::<_, 4>::from_vec(vec![1, 2]);
+        assert!(!inline.spilled());
+        assert_eq!(inline.slice(), [1, 2]);
+        // Unused inline slots do not take part in comparisons.
+        let mut other = SmallStorage::<_, 4>::from_elem(7, 2);
+        other.slice_mut().copy_from_slice(&[1, 2]);
+        assert_eq!(inline, other);
+
+        let heap = SmallStorage::<_, 1>::from_vec(vec![1, 2]);
+        assert!(heap.spilled());
+        assert_eq!(heap.slice(), [1, 2]);
+    }
+}
@@ -0,0 +1,311 @@
+use core::str;
+
+use super::fixed::Fixed;
+use super::record_ring::{RecordTooLarge, PREFIX_LEN};
+use super::traits::{Slice, SliceMut};
+
+/// Length prefix marking padding that runs to the physical end of the
+/// storage.
+const PADDING: u32 = u32::MAX;
+
+/// Strings in a byte ring, without an allocation per string.
+///
+/// Like `RecordRing`, each string is stored as a little-endian `u32` length
+/// followed by its bytes, and pushing evicts whole oldest strings until the
+/// new one fits. Unlike there, a string never crosses the physical end of the
+/// storage: the space left before the end is padded instead, so every string
+/// can be handed out as one `&str`.
+#[derive(Clone, Debug)]
+pub struct StringRing<S> {
+    bytes: Fixed<S>,
+    used: usize,
+    count: usize,
+}
+
+impl<S> StringRing<S>
+where
+    S: Slice<Element = u8>,
+{
+    /// Creates an empty string ring over `storage`.
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        StringRing {
+            bytes: Fixed::from(storage),
+            used: 0,
+            count: 0,
+        }
+    }
+
+    /// Longest string that fits in the ring along with its prefix.
+    #[inline]
+    pub fn max_str_len(&self) -> usize {
+        self.bytes
+            .len()
+            .saturating_sub(PREFIX_LEN)
+            .min(PADDING as usize - 1)
+    }
+
+    /// Number of strings held.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Number of bytes used by the strings held, prefixes and padding
+    /// included.
+    #[inline]
+    pub fn bytes_used(&self) -> usize {
+        self.used
+    }
+
+    /// Strings, oldest first.
+    #[inline]
+    pub fn iter(&self) -> Strs<'_, S> {
+        Strs {
+            bytes: &self.bytes,
+            pos: self.bytes.len() - self.used,
+            remaining: self.count,
+        }
+    }
+}
+
+impl<S> StringRing<S>
+where
+    S: SliceMut<Element = u8>,
+{
+    /// Appends `s`, evicting whole oldest strings until it fits. Returns the
+    /// number of strings evicted.
+    pub fn push_str(&mut self, s: &str) -> Result<usize, RecordTooLarge> {
+        let len = s.len();
+        // Storage shorter than a prefix cannot even hold the empty string.
+        if PREFIX_LEN + len > self.bytes.len() || len > self.max_str_len() {
+            return Err(RecordTooLarge {
+                len,
+                max: self.max_str_len(),
+            });
+        }
+
+        let mut evicted = 0;
+        let needed = PREFIX_LEN + len;
+        let mut padding = self.padding_before(needed);
+        if padding + needed > self.bytes.len() {
+            // Padding and string only fit once everything else is gone and
+            // the string can start at the physical start.
+            evicted = self.count;
+            self.clear();
+            self.bytes.set_first(0);
+            padding = 0;
+        }
+        while self.bytes.len() - self.used < padding + needed {
+            evicted += self.evict_oldest();
+        }
+
+        let mut filler = padding;
+        if padding >= PREFIX_LEN {
+            self.bytes.extend_from_slice(&PADDING.to_le_bytes());
+            filler -= PREFIX_LEN;
+        }
+        for _ in 0..filler {
+            self.bytes.push_overwrite(0);
+        }
+        self.bytes.extend_from_slice(&(len as u32).to_le_bytes());
+        self.bytes.extend_from_slice(s.as_bytes());
+        self.used += padding + needed;
+        self.count += 1;
+        Ok(evicted)
+    }
+
+    /// Drops every string.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.used = 0;
+        self.count = 0;
+    }
+
+    /// Bytes to pad before writing `needed` bytes so they do not cross the
+    /// physical end.
+    #[inline]
+    fn padding_before(&self, needed: usize) -> usize {
+        let room = self.bytes.len() - self.bytes.first();
+        if needed > room {
+            room
+        } else {
+            0
+        }
+    }
+
+    /// Drops the oldest string or padding; returns how many strings that
+    /// was.
+    fn evict_oldest(&mut self) -> usize {
+        let pos = self.bytes.len() - self.used;
+        match entry_at(&self.bytes, pos) {
+            Entry::Padding(len) => {
+                self.used -= len;
+                0
+            }
+            Entry::Str(len) => {
+                self.used -= PREFIX_LEN + len;
+                self.count -= 1;
+                1
+            }
+        }
+    }
+}
+
+enum Entry {
+    /// Padding of this many bytes, prefix included.
+    Padding(usize),
+    /// String of this many bytes, prefix excluded.
+    Str(usize),
+}
+
+#[inline]
+fn physical<S>(bytes: &Fixed<S>, pos: usize) -> usize
+where
+    S: Slice<Element = u8>,
+{
+    (bytes.first() + pos) % bytes.len()
+}
+
+/// The entry starting at logical `pos`.
+fn entry_at<S>(bytes: &Fixed<S>, pos: usize) -> Entry
+where
+    S: Slice<Element = u8>,
+{
+    let room = bytes.len() - physical(bytes, pos);
+    if room < PREFIX_LEN {
+        return Entry::Padding(room);
+    }
+    let mut prefix = [0; PREFIX_LEN];
+    bytes.copy_out(pos, &mut prefix);
+    match u32::from_le_bytes(prefix) {
+        PADDING => Entry::Padding(room),
+        len => Entry::Str(len as usize),
+    }
+}
+
+/// Iterator over the strings of a `StringRing`, oldest first.
+#[derive(Debug)]
+pub struct Strs<'a, S> {
+    bytes: &'a Fixed<S>,
+    pos: usize,
+    remaining: usize,
+}
+
+impl<'a, S> Iterator for Strs<'a, S>
+where
+    S: Slice<Element = u8>,
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            match entry_at(self.bytes, self.pos) {
+                Entry::Padding(len) => self.pos += len,
+                Entry::Str(len) => {
+                    let start = physical(self.bytes, self.pos + PREFIX_LEN);
+                    let bytes: &'a [u8] = self.bytes.storage().slice();
+                    self.pos += PREFIX_LEN + len;
+                    self.remaining -= 1;
+                    // SAFETY: the bytes were copied from a `&str` by
+                    // `push_str`, and strings never cross the physical end.
+                    return Some(unsafe { str::from_utf8_unchecked(&bytes[start..start + len]) });
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<S> ExactSizeIterator for Strs<'_, S> where S: Slice<Element = u8> {}
+
+impl<'a, S> IntoIterator for &'a StringRing<S>
+where
+    S: Slice<Element = u8>,
+{
+    type Item = &'a str;
+    type IntoIter = Strs<'a, S>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings<S: Slice<Element = u8>>(ring: &StringRing<S>) -> Vec<&str> {
+        ring.iter().collect()
+    }
+
+    #[test]
+    fn test_push_str() {
+        let mut ring = StringRing::new([0; 16]);
+        assert!(ring.is_empty());
+        assert_eq!(ring.push_str("héllo"), Ok(0));
+        assert_eq!(ring.push_str(""), Ok(0));
+        assert_eq!(strings(&ring), ["héllo", ""]);
+        assert_eq!(ring.bytes_used(), 14);
+        // The 2 bytes left before the end are padding.
+        assert_eq!(ring.push_str("ab"), Ok(1));
+        assert_eq!(strings(&ring), ["", "ab"]);
+        assert_eq!(ring.bytes_used(), 12);
+        assert_eq!(ring.push_str("xyz"), Ok(1));
+        assert_eq!(strings(&ring), ["ab", "xyz"]);
+        assert_eq!(
+            ring.push_str("much too long"),
+            Err(RecordTooLarge { len: 13, max: 12 })
+        );
+    }
+
+    #[test]
+    fn test_padding_with_prefix() {
+        let mut ring = StringRing::new(vec![0; 20]);
+        for s in ["a", "b", "c"] {
+            ring.push_str(s).unwrap();
+        }
+        // The 5 bytes left before the end are a padding prefix and a byte.
+        assert_eq!(ring.push_str("four"), Ok(2));
+        assert_eq!(strings(&ring), ["c", "four"]);
+        assert_eq!(ring.bytes_used(), 5 + 5 + 8);
+        assert_eq!(ring.push_str("e"), Ok(1));
+        assert_eq!(strings(&ring), ["four", "e"]);
+        // Evicts only the padding.
+        assert_eq!(ring.push_str("f"), Ok(0));
+        assert_eq!(strings(&ring), ["four", "e", "f"]);
+    }
+
+    #[test]
+    fn test_string_needing_whole_ring() {
+        let mut ring = StringRing::new([0; 10]);
+        ring.push_str("a").unwrap();
+        assert_eq!(ring.push_str("123456"), Ok(1));
+        assert_eq!(strings(&ring), ["123456"]);
+        ring.clear();
+        assert_eq!(ring.len(), 0);
+        assert_eq!(ring.iter().next(), None);
+    }
+
+    #[test]
+    fn test_storage_shorter_than_prefix() {
+        let mut ring = StringRing::new([0; 2]);
+        assert_eq!(ring.max_str_len(), 0);
+        assert_eq!(ring.push_str(""), Err(RecordTooLarge { len: 0, max: 0 }));
+        assert!(ring.is_empty());
+        assert_eq!(ring.iter().next(), None);
+    }
+}
@@ -0,0 +1,105 @@
+use core::fmt::{self, Write};
+use std::string::String;
+use std::sync::Arc;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use super::recorder::Recorder;
+use super::Vec;
+
+/// `tracing` layer that keeps the most recent events in a ring.
+///
+/// Every event is formatted into one line (`LEVEL target: message key=value`)
+/// and stored in a shared `Recorder`. The layer is cheap to clone: keep a clone
+/// around after installing the subscriber and call `dump` when something goes
+/// wrong.
+#[derive(Clone, Debug)]
+pub struct FlightRecorder {
+    recorder: Arc<Recorder>,
+}
+
+impl FlightRecorder {
+    /// Creates a layer keeping the last `capacity` events.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        FlightRecorder {
+            recorder: Arc::new(Recorder::new(capacity)),
+        }
+    }
+
+    #[inline]
+    pub fn recorder(&self) -> &Arc<Recorder> {
+        &self.recorder
+    }
+
+    /// Copies out the recorded events, oldest first.
+    #[inline]
+    pub fn dump(&self) -> Vec<String> {
+        self.recorder.dump()
+    }
+
+    /// Takes the recorded events, oldest first.
+    #[inline]
+    pub fn drain(&self) -> Vec<String> {
+        self.recorder.drain()
+    }
+}
+
+impl<S> Layer<S> for FlightRecorder
+where
+    S: Subscriber,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = String::new();
+        let _ = write!(line, "{} {}:", metadata.level(), metadata.target());
+        event.record(&mut LineVisitor(&mut line));
+        self.recorder.record(line);
+    }
+}
+
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {}", value);
+        } else {
+            let _ = write!(self.0, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_records_events() {
+        let layer = FlightRecorder::new(2);
+        let subscriber = tracing_subscriber::registry().with(layer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("first");
+            tracing::warn!(code = 7, "second");
+            tracing::error!(path = "/tmp", "third {}", 3);
+        });
+        assert_eq!(
+            layer.dump(),
+            [
+                "WARN rust_ringbuf::tracing_layer::test: second code=7",
+                "ERROR rust_ringbuf::tracing_layer::test: third 3 path=/tmp",
+            ]
+        );
+    }
+}
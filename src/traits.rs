@@ -0,0 +1,149 @@
+use super::{Box, Vec};
+
+pub trait Slice {
+    type Element;
+
+    fn slice(&self) -> &[Self::Element];
+}
+
+pub trait SliceMut: Slice {
+    fn slice_mut(&mut self) -> &mut [Self::Element];
+}
+
+/// Storage that can be rebuilt from a list of elements, used when
+/// deserializing rings.
+pub trait FromElements: Slice + Sized {
+    /// Length every value of the storage type has, if the type fixes it.
+    const FIXED_LEN: Option<usize>;
+
+    /// Builds the storage, or returns `None` if the length does not fit.
+    fn from_elements(elements: Vec<Self::Element>) -> Option<Self>;
+}
+
+pub trait FixedSizeArray {
+    const LEN: usize;
+}
+
+impl<T> Slice for &[T] {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[Self::Element] {
+        self
+    }
+}
+
+impl<T> Slice for &mut [T] {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[Self::Element] {
+        self
+    }
+}
+
+impl<T> SliceMut for &mut [T] {
+    #[inline]
+    fn slice_mut(&mut self) -> &mut [Self::Element] {
+        self
+    }
+}
+
+impl<T> Slice for Box<[T]> {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[Self::Element] {
+        &self[..]
+    }
+}
+
+impl<T> SliceMut for Box<[T]> {
+    #[inline]
+    fn slice_mut(&mut self) -> &mut [Self::Element] {
+        &mut self[..]
+    }
+}
+
+impl<T> Slice for Vec<T> {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[Self::Element] {
+        &self[..]
+    }
+}
+
+impl<T> SliceMut for Vec<T> {
+    #[inline]
+    fn slice_mut(&mut self) -> &mut [Self::Element] {
+        &mut self[..]
+    }
+}
+
+impl<T, const N: usize> Slice for [T; N] {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[Self::Element] {
+        &self[..]
+    }
+}
+
+impl<T, const N: usize> SliceMut for [T; N] {
+    #[inline]
+    fn slice_mut(&mut self) -> &mut [Self::Element] {
+        &mut self[..]
+    }
+}
+
+impl<T> FromElements for Vec<T> {
+    const FIXED_LEN: Option<usize> = None;
+
+    #[inline]
+    fn from_elements(elements: Vec<T>) -> Option<Self> {
+        Some(elements)
+    }
+}
+
+impl<T> FromElements for Box<[T]> {
+    const FIXED_LEN: Option<usize> = None;
+
+    #[inline]
+    fn from_elements(elements: Vec<T>) -> Option<Self> {
+        Some(elements.into_boxed_slice())
+    }
+}
+
+impl<T, const N: usize> FromElements for [T; N] {
+    const FIXED_LEN: Option<usize> = Some(N);
+
+    #[inline]
+    fn from_elements(elements: Vec<T>) -> Option<Self> {
+        elements.try_into().ok()
+    }
+}
+
+impl<T, const N: usize> FixedSizeArray for [T; N] {
+    const LEN: usize = N;
+}
+
+#[cfg(feature = "rkyv")]
+impl<T> Slice for rkyv::vec::ArchivedVec<T> {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[Self::Element] {
+        self.as_slice()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T> Slice for rkyv::boxed::ArchivedBox<[T]> {
+    type Element = T;
+
+    #[inline]
+    fn slice(&self) -> &[Self::Element] {
+        self.get()
+    }
+}
@@ -0,0 +1,65 @@
+//! Integrity reports for rings stored on disk.
+
+use core::ops::Range;
+
+use super::Vec;
+
+/// What an integrity check concluded about a region of a file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// Framing and checksum are valid.
+    Intact,
+    /// Framing is valid but the region carries no checksum to check.
+    Unchecked,
+    /// The region is damaged and its contents cannot be trusted.
+    Corrupt,
+}
+
+/// A byte range of a file and its integrity status.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Region {
+    pub range: Range<usize>,
+    pub status: Status,
+    /// Sequence number or logical index of the record, if the region holds one.
+    pub seq: Option<u64>,
+}
+
+/// Result of walking a ring file, regions in file order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Report {
+    pub regions: Vec<Region>,
+}
+
+impl Report {
+    /// Whether no region is corrupt.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.corrupt().next().is_none()
+    }
+
+    /// Regions whose contents can be read back.
+    #[inline]
+    pub fn recoverable(&self) -> impl Iterator<Item = &Region> + '_ {
+        self.regions
+            .iter()
+            .filter(|region| region.status != Status::Corrupt)
+    }
+
+    /// Regions whose contents are lost.
+    #[inline]
+    pub fn corrupt(&self) -> impl Iterator<Item = &Region> + '_ {
+        self.regions
+            .iter()
+            .filter(|region| region.status == Status::Corrupt)
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, range: Range<usize>, status: Status, seq: Option<u64>) {
+        self.regions.push(Region { range, status, seq });
+    }
+
+    #[inline]
+    pub(crate) fn sort(&mut self) {
+        self.regions.sort_by_key(|region| region.range.start);
+    }
+}
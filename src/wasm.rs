@@ -0,0 +1,99 @@
+//! WebAssembly bindings for rolling windows of numeric samples.
+
+use js_sys::{Float64Array, Function};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use super::fixed::Fixed;
+use super::{Box, Vec};
+
+/// Fixed window of the latest `f64` samples, exported to JavaScript as
+/// `WasmRingBuffer`.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct WasmRingBuffer {
+    inner: Fixed<Box<[f64]>>,
+}
+
+#[wasm_bindgen]
+impl WasmRingBuffer {
+    /// Creates a window of `capacity` samples, all `fill`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(capacity: usize, fill: f64) -> Result<WasmRingBuffer, JsError> {
+        if capacity == 0 {
+            return Err(JsError::new("capacity must be positive"));
+        }
+        Ok(WasmRingBuffer {
+            inner: Fixed::from(alloc::vec![fill; capacity].into_boxed_slice()),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Appends `value` and returns the evicted oldest sample.
+    pub fn push(&mut self, value: f64) -> f64 {
+        self.inner.push(value)
+    }
+
+    /// Appends every sample of `values`, oldest first.
+    #[wasm_bindgen(js_name = pushMany)]
+    pub fn push_many(&mut self, values: &[f64]) {
+        self.inner.extend_from_slice(values);
+    }
+
+    /// Sample at `index`, counting from the oldest one.
+    pub fn get(&self, index: usize) -> Option<f64> {
+        (index < self.inner.len()).then(|| *self.inner.get(index))
+    }
+
+    /// Copies the samples, oldest first. Returned to JavaScript as a
+    /// `Float64Array`.
+    #[wasm_bindgen(js_name = toVec)]
+    pub fn to_vec(&self) -> Vec<f64> {
+        self.inner.iter().copied().collect()
+    }
+
+    /// Copies the samples, oldest first, into a new `Float64Array`.
+    #[wasm_bindgen(js_name = toFloat64Array)]
+    pub fn to_float64_array(&self) -> Float64Array {
+        let (first, second) = self.inner.slices();
+        let array = Float64Array::new_with_length(self.inner.len() as u32);
+        array.subarray(0, first.len() as u32).copy_from(first);
+        array
+            .subarray(first.len() as u32, self.inner.len() as u32)
+            .copy_from(second);
+        array
+    }
+
+    /// Calls `callback(value, index)` for every sample, oldest first.
+    #[wasm_bindgen(js_name = forEach)]
+    pub fn for_each(&self, callback: &Function) -> Result<(), JsValue> {
+        for (index, &value) in self.inner.iter().enumerate() {
+            callback.call2(
+                &JsValue::NULL,
+                &JsValue::from_f64(value),
+                &JsValue::from_f64(index as f64),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        let mut rb = WasmRingBuffer::new(3, 0.0).unwrap();
+        assert_eq!(rb.length(), 3);
+        assert_eq!(rb.push(1.5), 0.0);
+        rb.push_many(&[2.5, 3.5, 4.5]);
+        assert_eq!(rb.get(0), Some(2.5));
+        assert_eq!(rb.get(3), None);
+        assert_eq!(rb.to_vec(), [2.5, 3.5, 4.5]);
+    }
+}
@@ -0,0 +1,138 @@
+//! Window snapshots as an async `Stream`, for streaming analytics.
+
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::sync::Arc;
+
+use futures_core::Stream;
+
+use super::fixed::Fixed;
+use super::latest::{Latest, Watcher};
+use super::traits::SliceMut;
+
+/// Ring that publishes a snapshot of its window every `every` pushes.
+///
+/// Snapshots are shared through an `Arc`, so each one is cloned from the ring
+/// once however many streams receive it. A slow stream skips to the newest
+/// snapshot instead of queueing the ones it missed.
+#[derive(Debug)]
+pub struct SnapshotRing<S> {
+    ring: Fixed<S>,
+    every: usize,
+    pending: usize,
+    latest: Latest<Arc<Fixed<S>>>,
+}
+
+impl<S> SnapshotRing<S>
+where
+    S: SliceMut + Clone,
+{
+    /// Publishes after every `every` pushes. Panics if `every` is zero.
+    pub fn new(ring: Fixed<S>, every: usize) -> Self {
+        assert!(every > 0);
+        SnapshotRing {
+            ring,
+            every,
+            pending: 0,
+            latest: Latest::new(),
+        }
+    }
+
+    #[inline]
+    pub fn ring(&self) -> &Fixed<S> {
+        &self.ring
+    }
+
+    pub fn push(&mut self, item: S::Element) -> S::Element {
+        let evicted = self.ring.push(item);
+        self.pending += 1;
+        if self.pending == self.every {
+            self.publish();
+        }
+        evicted
+    }
+
+    /// Publishes the current window right away and restarts the count.
+    pub fn publish(&mut self) {
+        self.pending = 0;
+        self.latest.set(Arc::new(self.ring.clone()));
+    }
+
+    /// Stream of the snapshots published from now on. It ends once the ring
+    /// is dropped.
+    #[inline]
+    pub fn snapshots(&self) -> Snapshots<S> {
+        Snapshots {
+            watcher: self.latest.watch(),
+        }
+    }
+}
+
+/// Stream returned by `SnapshotRing::snapshots`.
+#[derive(Debug)]
+pub struct Snapshots<S> {
+    watcher: Watcher<Arc<Fixed<S>>>,
+}
+
+impl<S> Stream for Snapshots<S> {
+    type Item = Arc<Fixed<S>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let watcher = &mut self.get_mut().watcher;
+        match watcher.poll_changed(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(watcher.get_latest()),
+            Poll::Ready(Err(_)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::task::Waker;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn next<S>(stream: &mut Snapshots<S>) -> Option<Arc<Fixed<S>>> {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(item) = Pin::new(&mut *stream).poll_next(&mut cx) {
+                return item;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let mut ring = SnapshotRing::new(Fixed::from([0; 3]), 2);
+        let mut stream = ring.snapshots();
+        ring.push(1);
+        ring.push(2);
+        assert!(next(&mut stream).unwrap().iter().eq(&[0, 1, 2]));
+
+        // A slow stream only sees the newest snapshot.
+        for i in 3..=6 {
+            ring.push(i);
+        }
+        assert!(next(&mut stream).unwrap().iter().eq(&[4, 5, 6]));
+
+        ring.push(7);
+        ring.publish();
+        let snapshot = next(&mut stream).unwrap();
+        assert!(snapshot.iter().eq(&[5, 6, 7]));
+        drop(ring);
+        assert!(next(&mut stream).is_none());
+        assert!(snapshot.iter().eq(&[5, 6, 7]));
+    }
+}
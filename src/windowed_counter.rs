@@ -0,0 +1,143 @@
+use super::clock::Clock;
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// Event counter over a sliding window split into fixed-length time buckets.
+///
+/// Each element of the backing storage is the count of one bucket of
+/// `bucket_len` clock ticks. The newest bucket lives at the last logical index;
+/// as time advances, buckets are rotated out by pushing empty ones, so the
+/// window always covers the last `len * bucket_len` ticks.
+#[derive(Clone, Debug)]
+pub struct WindowedCounter<S, C> {
+    buckets: Fixed<S>,
+    bucket_len: u64,
+    current: u64,
+    clock: C,
+}
+
+impl<S, C> WindowedCounter<S, C>
+where
+    S: SliceMut<Element = u64>,
+    C: Clock,
+{
+    /// Creates a counter over `storage`, which is reset to zero.
+    ///
+    /// Panics if `storage` is empty or `bucket_len` is zero.
+    pub fn new(storage: S, bucket_len: u64, clock: C) -> Self {
+        assert!(bucket_len > 0);
+        let mut buckets = Fixed::from(storage);
+        buckets.iter_mut().for_each(|count| *count = 0);
+        let current = clock.now() / bucket_len;
+        WindowedCounter {
+            buckets,
+            bucket_len,
+            current,
+            clock,
+        }
+    }
+
+    #[inline]
+    pub fn bucket_len(&self) -> u64 {
+        self.bucket_len
+    }
+
+    /// Length of the whole window in clock ticks.
+    #[inline]
+    pub fn window(&self) -> u64 {
+        self.buckets.len() as u64 * self.bucket_len
+    }
+
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    #[inline]
+    pub fn increment(&mut self) {
+        self.add(1);
+    }
+
+    /// Adds `n` events to the bucket of the current time.
+    pub fn add(&mut self, n: u64) {
+        let bucket = self.clock.now() / self.bucket_len;
+        if bucket > self.current {
+            let expired = self.expired_at(bucket);
+            for _ in 0..expired {
+                self.buckets.push(0);
+            }
+            self.current = bucket;
+        }
+
+        let newest = self.buckets.len() - 1;
+        self.buckets[newest] += n;
+    }
+
+    /// Total number of events in the window ending at the current time.
+    pub fn sum(&self) -> u64 {
+        let expired = self.expired_at(self.clock.now() / self.bucket_len);
+        self.buckets.iter().skip(expired).sum()
+    }
+
+    /// Average number of events per clock tick over the window.
+    #[inline]
+    pub fn rate(&self) -> f64 {
+        self.sum() as f64 / self.window() as f64
+    }
+
+    /// Drops all counted events.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buckets.iter_mut().for_each(|count| *count = 0);
+    }
+
+    fn expired_at(&self, bucket: u64) -> usize {
+        let elapsed = bucket.saturating_sub(self.current);
+        elapsed.min(self.buckets.len() as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn test_sum_within_bucket() {
+        let now = Cell::new(0);
+        let mut counter = WindowedCounter::new([7u64; 4], 10, || now.get());
+        assert_eq!(counter.sum(), 0);
+        counter.increment();
+        counter.add(4);
+        assert_eq!(counter.sum(), 5);
+        assert_eq!(counter.window(), 40);
+    }
+
+    #[test]
+    fn test_buckets_rotate() {
+        let now = Cell::new(0);
+        let mut counter = WindowedCounter::new([0u64; 3], 10, || now.get());
+        counter.add(1);
+        now.set(10);
+        counter.add(2);
+        now.set(25);
+        counter.add(4);
+        assert_eq!(counter.sum(), 7);
+        now.set(30);
+        assert_eq!(counter.sum(), 6);
+        now.set(45);
+        assert_eq!(counter.sum(), 4);
+        now.set(1000);
+        assert_eq!(counter.sum(), 0);
+        counter.increment();
+        assert_eq!(counter.sum(), 1);
+    }
+
+    #[test]
+    fn test_rate() {
+        let now = Cell::new(0);
+        let mut counter = WindowedCounter::new(vec![0u64; 5], 2, || now.get());
+        counter.add(5);
+        assert_eq!(counter.rate(), 0.5);
+    }
+}
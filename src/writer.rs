@@ -0,0 +1,187 @@
+use core::fmt;
+
+use super::fixed::Fixed;
+use super::traits::SliceMut;
+
+/// `core::fmt::Write` adapter over a byte ring.
+///
+/// Formatted output lands in a bounded buffer that keeps the most recent
+/// bytes, so `write!` diagnostics can be captured without allocation and
+/// dumped later (e.g. over RTT or a UART). Writing never fails; once the ring
+/// is full the oldest bytes are overwritten, which may cut the oldest text in
+/// the middle of a UTF-8 sequence.
+#[derive(Clone, Debug)]
+pub struct RingWriter<S> {
+    ring: Fixed<S>,
+    written: usize,
+}
+
+impl<S> RingWriter<S>
+where
+    S: SliceMut<Element = u8>,
+{
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        RingWriter {
+            ring: Fixed::from(storage),
+            written: 0,
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Number of captured bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.written
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        let capacity = self.ring.len();
+        // Only the tail of an oversized write survives.
+        let bytes = &bytes[bytes.len().saturating_sub(capacity)..];
+        self.ring.extend_from_slice(bytes);
+        self.written = (self.written + bytes.len()).min(capacity);
+    }
+
+    /// The captured bytes, oldest first, as two slices.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let (start, end) = self.ring.slices();
+        let skip = self.ring.len() - self.written;
+        if skip < start.len() {
+            (&start[skip..], end)
+        } else {
+            (&end[skip - start.len()..], &[])
+        }
+    }
+
+    /// Copies the captured bytes into `dst` and returns how many were copied.
+    #[inline]
+    pub fn copy_to(&self, dst: &mut [u8]) -> usize {
+        self.read_at(0, dst)
+    }
+
+    /// Copies captured bytes from `offset` on, counting from the oldest one,
+    /// into `dst` and returns how many were copied; fewer than `dst.len()`
+    /// if the captured bytes end first.
+    pub fn read_at(&self, offset: usize, dst: &mut [u8]) -> usize {
+        let n = dst.len().min(self.written.saturating_sub(offset));
+        if n > 0 {
+            let start = self.ring.len() - self.written + offset;
+            self.ring.copy_out(start, &mut dst[..n]);
+        }
+        n
+    }
+
+    /// Overwrites captured bytes from `offset` on, counting from the oldest
+    /// one, for patching a length field after the fact. Panics unless all of
+    /// `src` lands on captured bytes.
+    pub fn write_at(&mut self, offset: usize, src: &[u8]) {
+        assert!(
+            offset <= self.written && src.len() <= self.written - offset,
+            "write past the captured bytes"
+        );
+        let start = self.ring.len() - self.written + offset;
+        self.ring.copy_in(start, src);
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.written = 0;
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Fixed<S> {
+        self.ring
+    }
+}
+
+impl<S> fmt::Write for RingWriter<S>
+where
+    S: SliceMut<Element = u8>,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::fmt::Write;
+
+    fn captured<S: SliceMut<Element = u8>>(writer: &RingWriter<S>) -> Vec<u8> {
+        let (a, b) = writer.as_slices();
+        [a, b].concat()
+    }
+
+    #[test]
+    fn test_write_fits() {
+        let mut writer = RingWriter::new([0u8; 16]);
+        write!(writer, "t={} ok", 42).unwrap();
+        assert_eq!(captured(&writer), b"t=42 ok");
+        assert_eq!(writer.len(), 7);
+    }
+
+    #[test]
+    fn test_keeps_latest() {
+        let mut writer = RingWriter::new([0u8; 8]);
+        writeln!(writer, "first").unwrap();
+        writeln!(writer, "second").unwrap();
+        assert_eq!(captured(&writer), b"\nsecond\n");
+        writer.write_str("a very long line").unwrap();
+        assert_eq!(captured(&writer), b"ong line");
+
+        let mut dst = [0u8; 4];
+        assert_eq!(writer.copy_to(&mut dst), 4);
+        assert_eq!(&dst, b"ong ");
+        writer.clear();
+        assert!(captured(&writer).is_empty());
+    }
+
+    #[test]
+    fn test_write_at() {
+        let mut writer = RingWriter::new([0u8; 8]);
+        writer.write_bytes(b"xxxxxxx");
+        // A frame whose length field, still to be filled in, crosses the
+        // physical end.
+        writer.write_bytes(&[0, 0, b'a', b'b', b'c']);
+        writer.write_at(3, &[0, 3]);
+        assert_eq!(captured(&writer), b"xxx\0\x03abc");
+        writer.write_at(8, &[]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_write_at_past_captured() {
+        let mut writer = RingWriter::new([0u8; 8]);
+        writer.write_bytes(b"abc");
+        writer.write_at(2, b"de");
+    }
+
+    #[test]
+    fn test_read_at() {
+        let mut writer = RingWriter::new([0u8; 8]);
+        writer.write_bytes(b"xxxxxx");
+        writer.write_bytes(&[3, b'a', b'b', b'c']);
+        let mut header = [0u8; 1];
+        assert_eq!(writer.read_at(4, &mut header), 1);
+        let mut body = [0u8; 8];
+        assert_eq!(writer.read_at(5, &mut body), 3);
+        assert_eq!(&body[..3], b"abc");
+        assert_eq!(writer.read_at(8, &mut body), 0);
+        assert_eq!(writer.read_at(100, &mut body), 0);
+        // Reading consumed nothing.
+        assert_eq!(writer.len(), 8);
+    }
+}